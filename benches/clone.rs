@@ -0,0 +1,65 @@
+#[macro_use]
+extern crate criterion;
+extern crate heaparray;
+
+use criterion::Criterion;
+use heaparray::*;
+
+const LEN: usize = 1_000_000;
+
+fn clone_copy_elements(c: &mut Criterion) {
+    let array: HeapArray<u64> = HeapArray::new(LEN, |i| i as u64);
+    let copy_array = array.clone();
+    c.bench_function("HeapArray<u64> clone", move |b| b.iter(|| array.clone()));
+    c.bench_function("HeapArray<u64> clone_copy", move |b| {
+        b.iter(|| copy_array.clone_copy())
+    });
+    let vec: Vec<u64> = (0..LEN as u64).collect();
+    c.bench_function("Vec<u64> clone", move |b| b.iter(|| vec.clone()));
+}
+
+fn clone_into_existing(c: &mut Criterion) {
+    let array: HeapArray<u64> = HeapArray::new(LEN, |i| i as u64);
+    let mut target: HeapArray<u64> = HeapArray::new(LEN, |_| 0);
+    c.bench_function("HeapArray<u64> clone_into", move |b| {
+        b.iter(|| array.clone_into(&mut target))
+    });
+    let vec: Vec<u64> = (0..LEN as u64).collect();
+    let mut target: Vec<u64> = vec![0; LEN];
+    c.bench_function("Vec<u64> clone_from", move |b| {
+        b.iter(|| target.clone_from(&vec))
+    });
+}
+
+fn clone_into_resized(c: &mut Criterion) {
+    let sources: Vec<HeapArray<u64>> = (0..16)
+        .map(|i| HeapArray::new(LEN / 16 * (i % 5 + 1), |j| j as u64))
+        .collect();
+    let mut target: HeapArray<u64> = HeapArray::new(0, |_| 0);
+    c.bench_function("HeapArray<u64> clone_into varying lengths", move |b| {
+        b.iter(|| {
+            for source in &sources {
+                source.clone_into(&mut target);
+            }
+        })
+    });
+    let sources: Vec<Vec<u64>> = (0..16)
+        .map(|i| (0..(LEN / 16 * (i % 5 + 1)) as u64).collect())
+        .collect();
+    let mut target: Vec<u64> = Vec::new();
+    c.bench_function("Vec<u64> clone_from varying lengths", move |b| {
+        b.iter(|| {
+            for source in &sources {
+                target.clone_from(source);
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    clone_copy_elements,
+    clone_into_existing,
+    clone_into_resized
+);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+#[macro_use]
+extern crate criterion;
+extern crate heaparray;
+
+use criterion::Criterion;
+use heaparray::*;
+
+const LEN: usize = 1_000_000;
+
+fn zeroed(c: &mut Criterion) {
+    c.bench_function("HeapArray<u8> new_zeroed", |b| {
+        b.iter(|| HeapArray::<u8>::new_zeroed(LEN))
+    });
+    c.bench_function("HeapArray<u8> new", |b| {
+        b.iter(|| HeapArray::<u8>::new(LEN, |_| 0u8))
+    });
+}
+
+fn with_len(c: &mut Criterion) {
+    c.bench_function("HeapArray<u64> with_len", |b| {
+        b.iter(|| HeapArray::<u64>::with_len((), LEN))
+    });
+    c.bench_function("HeapArray<u64> with_len_zeroed", |b| {
+        b.iter(|| HeapArray::<u64>::with_len_zeroed((), LEN))
+    });
+}
+
+criterion_group!(benches, zeroed, with_len);
+criterion_main!(benches);
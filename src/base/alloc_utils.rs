@@ -15,14 +15,60 @@ unsafe impl alloc::GlobalAlloc for Global {
     unsafe fn alloc(&self, layout: alloc::Layout) -> *mut u8 {
         alloc::alloc(layout)
     }
+    unsafe fn alloc_zeroed(&self, layout: alloc::Layout) -> *mut u8 {
+        alloc::alloc_zeroed(layout)
+    }
     unsafe fn dealloc(&self, ptr: *mut u8, layout: alloc::Layout) {
         alloc::dealloc(ptr, layout);
     }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: alloc::Layout, new_size: usize) -> *mut u8 {
+        alloc::realloc(ptr, layout, new_size)
+    }
+}
+
+// Allocators can't be asked for zero-sized blocks, so the functions below hand
+// out a dangling, aligned pointer for those instead, and never free it.
+
+/// Returns a non-null pointer with the alignment of `layout`, that doesn't point
+/// to any allocation.
+fn dangling<T>(layout: Layout) -> *mut T {
+    layout.align() as *mut T
 }
 
 /// Allocate a block of memory, and then coerce it to type `T`
 pub unsafe fn allocate<T>(layout: Layout, allocator: impl alloc::GlobalAlloc) -> *mut T {
-    allocator.alloc(layout) as *mut T
+    if layout.size() == 0 {
+        dangling(layout)
+    } else {
+        allocator.alloc(layout) as *mut T
+    }
+}
+
+/// Allocate a block of zeroed memory, and then coerce it to type `T`
+pub unsafe fn allocate_zeroed<T>(layout: Layout, allocator: impl alloc::GlobalAlloc) -> *mut T {
+    if layout.size() == 0 {
+        dangling(layout)
+    } else {
+        allocator.alloc_zeroed(layout) as *mut T
+    }
+}
+
+/// Resize a block of memory to `new_size` bytes, and then coerce it to type `T`
+pub unsafe fn reallocate<T>(
+    ptr: *mut T,
+    layout: Layout,
+    new_size: usize,
+    allocator: impl alloc::GlobalAlloc,
+) -> *mut T {
+    let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+    if layout.size() == 0 {
+        allocate(new_layout, allocator)
+    } else if new_size == 0 {
+        deallocate(ptr, layout, allocator);
+        dangling(new_layout)
+    } else {
+        allocator.realloc(ptr as *mut u8, layout, new_size) as *mut T
+    }
 }
 
 /// Deallocate a block of memory using the given size and alignment information.
@@ -30,7 +76,9 @@ pub unsafe fn allocate<T>(layout: Layout, allocator: impl alloc::GlobalAlloc) ->
 /// Completely ignores the type of the input pointer, so the layout
 /// needs to be correct.
 pub unsafe fn deallocate<T>(ptr: *mut T, layout: Layout, allocator: impl alloc::GlobalAlloc) {
-    allocator.dealloc(ptr as *mut u8, layout);
+    if layout.size() != 0 {
+        allocator.dealloc(ptr as *mut u8, layout);
+    }
 }
 
 /// Get the size and alignment, in bytes, of a type repeated `repeat` many times.
@@ -1,5 +1,6 @@
 use super::mem_block::*;
 use super::traits::*;
+use core::iter::FusedIterator;
 use core::marker::PhantomData;
 use core::ptr::NonNull;
 use core::{mem, ptr};
@@ -31,13 +32,17 @@ where
 /// `BaseArray` can't be safely iterated over, so this object can only be constructed
 /// via the unsafe method `BaseArray::into_iter`, which takes as a parameter an
 /// associated length.
+///
+/// Always knows exactly how many elements it has left, and reports that number
+/// through `size_hint`, so that e.g. collecting into a `Vec` allocates only once.
 pub struct BaseArrayIter<E, L, P = NonNull<MemBlock<E, L>>>
 where
     P: BaseArrayPtr<E, L>,
 {
     array: BaseArray<E, L, P>,
-    current: *mut E,
-    end: *mut E,
+    current: usize,
+    end: usize,
+    len: usize,
 }
 
 impl<E, L, P> BaseArray<E, L, P>
@@ -46,7 +51,7 @@ where
 {
     /// Construct an instance of this struct from an instance of the pointer type
     /// `P`.
-    pub unsafe fn from_ptr(ptr: P) -> Self {
+    pub const unsafe fn from_ptr(ptr: P) -> Self {
         Self {
             data: ptr,
             phantom: PhantomData,
@@ -70,6 +75,29 @@ where
         array
     }
 
+    /// Doesn't initialize anything in the array. Just allocates a block of memory,
+    /// returning `None` if the allocation fails.
+    ///
+    /// # Safety
+    /// Neither the label nor the elements are initialized.
+    pub unsafe fn try_alloc(len: usize) -> Option<Self> {
+        let mut array = Self::from_ptr(P::try_alloc(len)?);
+        array.data._init();
+        Some(array)
+    }
+
+    /// Doesn't initialize anything in the array. Just allocates a block of memory,
+    /// whose elements are all set to zero.
+    ///
+    /// # Safety
+    /// The label is left uninitialized, and the elements are only valid if an
+    /// all-zero bit pattern is valid for `E`.
+    pub unsafe fn alloc_zeroed(len: usize) -> Self {
+        let mut array = Self::from_ptr(P::alloc_zeroed(len));
+        array.data._init();
+        array
+    }
+
     /// Doesn't initialize the elements of the array.
     pub unsafe fn new_lazy(label: L, len: usize) -> Self {
         let mut array = Self::alloc(len);
@@ -80,17 +108,25 @@ where
     /// Creates a new array of size `len`.
     ///
     /// Initializes all elements using the given function, and initializes the
-    /// label with the provided value.
+    /// label with the provided value. If the function panics, the elements that
+    /// were already initialized and the label are dropped, and the array is
+    /// deallocated.
     pub fn new<F>(label: L, len: usize, mut func: F) -> Self
     where
         F: FnMut(&mut L, usize) -> E,
     {
-        let array = unsafe { Self::new_lazy(label, len) };
+        let mut array = unsafe { Self::new_lazy(label, len) };
+        let mut guard = InitGuard {
+            array: &mut array,
+            len,
+            initialized: 0,
+        };
         for i in 0..len {
-            unsafe {
-                ptr::write(array.data.elem_ptr(i), func(&mut *array.data.lbl_ptr(), i));
-            }
+            let elem = func(guard.array.get_label_mut(), i);
+            unsafe { ptr::write(guard.array.get_ptr_mut(i), elem) };
+            guard.initialized += 1;
         }
+        mem::forget(guard);
         array
     }
 
@@ -99,12 +135,28 @@ where
     /// # Safety
     /// Function is safe as long as the underlying array is at least length `len`,
     /// and the elements in the array have been initialized.
+    ///
+    /// If the destructor of the label or of an element panics, the remaining
+    /// destructors are still run and the block is still deallocated.
     pub unsafe fn drop(&mut self, len: usize) {
-        ptr::drop_in_place(self.get_label_mut());
-        for i in 0..len {
-            ptr::drop_in_place(self.data.elem_ptr(i));
-        }
-        self.drop_lazy(len);
+        self.drop_range(len, 0, len);
+    }
+
+    /// Runs destructor code for the label and for the elements in `[start, end)`,
+    /// then deallocates block. Like `drop`, this keeps going if a destructor panics.
+    ///
+    /// # Safety
+    /// The underlying array must be of length `len`, and the label and the
+    /// elements in `[start, end)` must be initialized.
+    pub(crate) unsafe fn drop_range(&mut self, len: usize, start: usize, end: usize) {
+        let elements = ptr::slice_from_raw_parts_mut(self.data.elem_ptr(start), end - start);
+        let label = self.get_label_mut() as *mut L;
+
+        // Locals are dropped in reverse order, and the rest are still dropped
+        // while unwinding if one of them panics.
+        let _dealloc = DeallocGuard { array: self, len };
+        let _elements = DropGuard(elements);
+        let _label = DropGuard(label);
     }
 
     /// Deallocates block without running destructor code for elements or label.
@@ -116,6 +168,16 @@ where
         self.data.dealloc(len);
     }
 
+    /// Resizes the block of this array so that it can hold `new_len` elements,
+    /// preserving the label and the first `min(old_len, new_len)` elements.
+    ///
+    /// # Safety
+    /// The underlying array must be of length `old_len`, and the elements at index
+    /// `new_len` or above must have already been dropped or moved out.
+    pub unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        self.data.realloc(old_len, new_len);
+    }
+
     /// Cast this array into a different array.
     ///
     /// Doesn't alter the length information of the array at all, or perform
@@ -207,28 +269,87 @@ where
     ///
     /// The slice is from element 0 to `len - 1` inclusive.
     pub unsafe fn as_slice(&self, len: usize) -> &[E] {
-        core::slice::from_raw_parts(self.get(0), len)
+        core::slice::from_raw_parts(self.get_ptr(0), len)
     }
 
     /// Returns a mutable reference to a slice into this array.
     ///
     /// The slice is from element 0 to `len - 1` inclusive.
     pub unsafe fn as_slice_mut(&mut self, len: usize) -> &mut [E] {
-        core::slice::from_raw_parts_mut(self.get_mut(0), len)
+        core::slice::from_raw_parts_mut(self.get_ptr_mut(0), len)
+    }
+
+    /// Returns an iterator over references to the first `len` elements of this
+    /// array.
+    ///
+    /// # Safety
+    /// The underlying array must be at least length `len`, and its first `len`
+    /// elements must be initialized.
+    pub unsafe fn iter(&self, len: usize) -> core::slice::Iter<'_, E> {
+        self.as_slice(len).iter()
+    }
+
+    /// Returns an iterator over mutable references to the first `len` elements
+    /// of this array.
+    ///
+    /// # Safety
+    /// The underlying array must be at least length `len`, and its first `len`
+    /// elements must be initialized.
+    pub unsafe fn iter_mut(&mut self, len: usize) -> core::slice::IterMut<'_, E> {
+        self.as_slice_mut(len).iter_mut()
     }
 
     /// Returns an iterator into this array, consuming the array in the process.
-    pub unsafe fn into_iter(mut self, len: usize) -> BaseArrayIter<E, L, P> {
-        let current = self.get_mut(0) as *mut E;
-        let end = current.add(len);
+    ///
+    /// The iterator drops the label and any elements it hasn't yielded, and
+    /// deallocates the array, when it's dropped.
+    ///
+    /// # Safety
+    /// The underlying array must be of length `len`, and the label and elements
+    /// must be initialized.
+    pub unsafe fn into_iter(self, len: usize) -> BaseArrayIter<E, L, P> {
+        self.into_iter_range(len, 0, len)
+    }
+
+    /// Returns an iterator over the elements in `[start, end)` of this array,
+    /// consuming the array in the process.
+    ///
+    /// # Safety
+    /// The underlying array must be of length `len`, and the elements in
+    /// `[start, end)` must be initialized; all other elements must have already
+    /// been moved out or dropped, as the iterator won't drop them. The label must
+    /// be initialized, and is dropped along with the iterator.
+    pub unsafe fn into_iter_range(
+        self,
+        len: usize,
+        start: usize,
+        end: usize,
+    ) -> BaseArrayIter<E, L, P> {
         BaseArrayIter {
             array: self,
-            current,
+            current: start,
             end,
+            len,
         }
     }
 }
 
+impl<E, P> BaseArray<E, (), P>
+where
+    P: BaseArrayPtr<E, ()>,
+{
+    /// Creates a new, unlabelled array of size `len`, initializing each element
+    /// with `func`.
+    ///
+    /// Like `new`, this cleans up after itself if `func` panics.
+    pub fn new_init<F>(len: usize, mut func: F) -> Self
+    where
+        F: FnMut(usize) -> E,
+    {
+        Self::new((), len, |_, i| func(i))
+    }
+}
+
 impl<E, L, P> BaseArray<E, L, P>
 where
     E: Clone,
@@ -237,8 +358,82 @@ where
 {
     /// Clones the elements and label of this array into a new array of the same
     /// size.
+    ///
+    /// Like `new`, this cleans up after itself if cloning an element panics.
     pub unsafe fn clone(&self, len: usize) -> Self {
-        Self::new(self.get_label().clone(), len, |_, i| self.get(i).clone())
+        Self::new(self.get_label().clone(), len, |_, i| {
+            (*self.get_ptr(i)).clone()
+        })
+    }
+}
+
+impl<E, L, P> BaseArray<E, L, P>
+where
+    E: Copy,
+    L: Clone,
+    P: BaseArrayPtr<E, L>,
+{
+    /// Clones the label of this array, and copies its elements into a new array
+    /// of the same size with a single `ptr::copy_nonoverlapping`.
+    ///
+    /// # Safety
+    /// The underlying array must be at least length `len`, and its first `len`
+    /// elements and label must be initialized.
+    pub unsafe fn clone_copy(&self, len: usize) -> Self {
+        let mut array = Self::new_lazy(self.get_label().clone(), len);
+        ptr::copy_nonoverlapping(self.get_ptr(0), array.get_ptr_mut(0), len);
+        array
+    }
+}
+
+/// Guard for an array whose elements are being initialized.
+///
+/// If dropped, e.g. because initializing an element panicked, this drops the
+/// label and the first `initialized` elements of the array, and then
+/// deallocates it. Call `mem::forget` on it once the array is fully initialized.
+pub(crate) struct InitGuard<'a, E, L, P>
+where
+    P: BaseArrayPtr<E, L>,
+{
+    pub(crate) array: &'a mut BaseArray<E, L, P>,
+    pub(crate) len: usize,
+    pub(crate) initialized: usize,
+}
+
+impl<'a, E, L, P> Drop for InitGuard<'a, E, L, P>
+where
+    P: BaseArrayPtr<E, L>,
+{
+    fn drop(&mut self) {
+        unsafe { self.array.drop_range(self.len, 0, self.initialized) };
+    }
+}
+
+/// Runs the destructor of the value it points to when dropped.
+struct DropGuard<T: ?Sized>(*mut T);
+
+impl<T: ?Sized> Drop for DropGuard<T> {
+    fn drop(&mut self) {
+        unsafe { ptr::drop_in_place(self.0) };
+    }
+}
+
+/// Deallocates an array of length `len` without running any destructors when
+/// dropped.
+struct DeallocGuard<'a, E, L, P>
+where
+    P: BaseArrayPtr<E, L>,
+{
+    array: &'a mut BaseArray<E, L, P>,
+    len: usize,
+}
+
+impl<'a, E, L, P> Drop for DeallocGuard<'a, E, L, P>
+where
+    P: BaseArrayPtr<E, L>,
+{
+    fn drop(&mut self) {
+        unsafe { self.array.drop_lazy(self.len) };
     }
 }
 
@@ -251,13 +446,53 @@ where
         if self.current == self.end {
             None
         } else {
-            unsafe {
-                let out = Some(ptr::read(self.current));
-                self.current = self.current.add(1);
-                out
-            }
+            let out = unsafe { ptr::read(self.array.get_ptr(self.current)) };
+            self.current += 1;
+            Some(out)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.end - self.current;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<E, L, P> DoubleEndedIterator for BaseArrayIter<E, L, P>
+where
+    P: BaseArrayPtr<E, L>,
+{
+    fn next_back(&mut self) -> Option<E> {
+        if self.current == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some(unsafe { ptr::read(self.array.get_ptr(self.end)) })
+        }
+    }
+}
+
+impl<E, L, P> ExactSizeIterator for BaseArrayIter<E, L, P> where P: BaseArrayPtr<E, L> {}
+
+/// Once exhausted from either end, the iterator keeps returning `None`.
+impl<E, L, P> FusedIterator for BaseArrayIter<E, L, P> where P: BaseArrayPtr<E, L> {}
+
+/// Clones the elements the iterator hasn't yielded yet, along with the label,
+/// into a new block; the clone owns that block, and is dropped independently of
+/// the original.
+impl<E, L, P> Clone for BaseArrayIter<E, L, P>
+where
+    E: Clone,
+    L: Clone,
+    P: BaseArrayPtr<E, L>,
+{
+    fn clone(&self) -> Self {
+        let remaining = self.end - self.current;
+        let array = BaseArray::new(self.array.get_label().clone(), remaining, |_, i| unsafe {
+            (*self.array.get_ptr(self.current + i)).clone()
+        });
+        unsafe { array.into_iter(remaining) }
+    }
 }
 
 impl<E, L, P> Drop for BaseArrayIter<E, L, P>
@@ -265,8 +500,24 @@ where
     P: BaseArrayPtr<E, L>,
 {
     fn drop(&mut self) {
-        let begin = self.array.get_ptr_mut(0) as usize;
-        let len = ((self.end as usize) - begin) / mem::size_of::<E>();
-        unsafe { self.array.drop(len) }
+        unsafe { self.array.drop_range(self.len, self.current, self.end) };
     }
 }
+
+// The iterator owns its array, which `BaseArray` alone can't promise, so it can
+// be sent or shared whenever the elements, label and pointer can.
+unsafe impl<E, L, P> Send for BaseArrayIter<E, L, P>
+where
+    P: BaseArrayPtr<E, L> + Send,
+    E: Send,
+    L: Send,
+{
+}
+
+unsafe impl<E, L, P> Sync for BaseArrayIter<E, L, P>
+where
+    P: BaseArrayPtr<E, L> + Sync,
+    E: Sync,
+    L: Sync,
+{
+}
@@ -3,6 +3,7 @@
 
 use super::alloc_utils::*;
 use super::traits::*;
+use crate::error::CapacityError;
 use const_utils::{cond, max, safe_div};
 use core::alloc::Layout;
 use core::marker::PhantomData;
@@ -48,6 +49,9 @@ use core::sync::atomic::{AtomicPtr, Ordering};
 ///
 /// - A `MemBlock` cannot be larger than `core::isize::MAX` bytes
 /// - Allocation functions panic instead of returning null pointers
+/// - Zero-sized labels and elements take up no space, and a block that ends up
+///   taking no space at all (e.g. one with a zero-sized label and zero-sized
+///   elements) is a dangling, aligned pointer that never touches the allocator
 ///
 /// However, note that the above invariants can be disabled for better performance,
 /// as discussed below.
@@ -89,32 +93,119 @@ impl<E, L> MemBlock<E, L> {
         cond(mem::size_of::<E>() == 0, max_len, max_len_calc)
     }
 
+    /// Returns an error if a block of length `len` would be longer than
+    /// `MemBlock::max_len()`.
+    pub fn check_len(len: usize) -> Result<(), CapacityError> {
+        let max_len = Self::max_len();
+        if len > max_len {
+            Err(CapacityError { len, max_len })
+        } else {
+            Ok(())
+        }
+    }
+
     /// Get size and alignment of the memory that a block of length `len` would need.
     ///
-    /// Returns a tuple in the form `(size, align)`
+    /// Returns a tuple in the form `(size, align)`. Even when `len` is 0, the
+    /// block is big enough and aligned enough that the pointer to its elements
+    /// is in bounds and properly aligned. A zero-sized label adds nothing to
+    /// either the size or the alignment of the block.
     pub const fn memory_layout(len: usize) -> (usize, usize) {
-        let (l_size, l_align) = size_align::<L>(1);
-        let (calc_size, calc_align) = {
-            let (dsize, dalign) = size_align::<E>(len);
-            let l_size = aligned_size::<L>(dalign);
-            (l_size + dsize, max(l_align, dalign))
+        let (_, l_align) = size_align::<L>(1);
+        let (dsize, dalign) = size_align::<E>(len);
+        let l_size = aligned_size::<L>(dalign);
+        (l_size + dsize, max(l_align, dalign))
+    }
+
+    /// Get size and alignment of the memory that a block of length `len` would
+    /// need, or `None` if `len > MemBlock::max_len()` or the block's layout would
+    /// be invalid for this platform.
+    ///
+    /// Unlike `memory_layout`, this never overflows, so it can be used to
+    /// validate a length before allocating a block of that length.
+    pub fn checked_memory_layout(len: usize) -> Option<(usize, usize)> {
+        Self::check_len(len).ok()?;
+        let (_, l_align) = size_align::<L>(1);
+        let (e_size, e_align) = size_align::<E>(1);
+        let size = e_size
+            .checked_mul(len)?
+            .checked_add(aligned_size::<L>(e_align))?;
+        let layout = Layout::from_size_align(size, max(l_align, e_align)).ok()?;
+        Some((layout.size(), layout.align()))
+    }
+
+    /// Get size and alignment of the memory that a block of length `len` would
+    /// need, if its elements were aligned to at least `align` bytes.
+    ///
+    /// Returns a tuple in the form `(size, align)`
+    pub const fn memory_layout_aligned(len: usize, align: usize) -> (usize, usize) {
+        let (_, l_align) = size_align::<L>(1);
+        let (e_size, e_align) = size_align::<E>(len);
+        let align = max(align, e_align);
+        let offset = aligned_size::<L>(align);
+        (offset + e_size, max(l_align, align))
+    }
+
+    /// Get the maximum length of a block whose elements are aligned to at least
+    /// `align` bytes; like `max_len`, but accounting for the padding after the
+    /// label.
+    pub const fn max_len_aligned(align: usize) -> usize {
+        let max_len = isize::MAX as usize;
+        let max_len_calc = {
+            let (esize, ealign) = size_align::<E>(1);
+            let lsize = aligned_size::<L>(max(align, ealign));
+            safe_div(max_len - lsize, esize)
         };
-        (
-            cond(len == 0, l_size, calc_size),
-            cond(len == 0, l_align, calc_align),
-        )
+        cond(mem::size_of::<E>() == 0, max_len, max_len_calc)
+    }
+
+    /// Returns an error if a block of length `len` whose elements are aligned to
+    /// at least `align` bytes would be longer than `MemBlock::max_len_aligned()`.
+    pub fn check_len_aligned(len: usize, align: usize) -> Result<(), CapacityError> {
+        let max_len = Self::max_len_aligned(align);
+        if len > max_len {
+            Err(CapacityError { len, max_len })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Allocates a block of length `len` whose elements are aligned to at least
+    /// `align` bytes.
+    ///
+    /// # Safety
+    /// The block must be deallocated with `dealloc_aligned`, using the same
+    /// values of `len` and `align`.
+    pub(crate) unsafe fn alloc_aligned(len: usize, align: usize) -> NonNull<Self> {
+        let layout = get_layout_aligned::<E, L>(len, align);
+        NonNull::new_unchecked(check_ptr(allocate(layout, Global)))
+    }
+
+    /// Deallocates a block allocated with `alloc_aligned`.
+    ///
+    /// # Safety
+    /// `len` and `align` must be the values that the block was allocated with.
+    pub(crate) unsafe fn dealloc_aligned(ptr: NonNull<Self>, len: usize, align: usize) {
+        let layout = get_layout_aligned::<E, L>(len, align);
+        deallocate(ptr.as_ptr(), layout, Global);
+    }
+
+    /// Returns a pointer to the element at index `idx` of a block whose elements
+    /// are aligned to at least `align` bytes.
+    pub(crate) fn elem_ptr_aligned(ptr: NonNull<Self>, idx: usize, align: usize) -> *mut E {
+        check_len::<E, L>(idx + 1);
+        let offset = aligned_size::<L>(max(align, mem::align_of::<E>()));
+        let element = unsafe { (ptr.as_ptr() as *mut u8).add(offset) as *mut E };
+        unsafe { element.add(idx) }
     }
 }
 
 /// Make sure that a `MemBlock<E, L>` of length `len` isn't too big
 fn check_len<E, L>(len: usize) {
-    if cfg!(not(feature = "mem-block-skip-size-check")) && len > MemBlock::<E, L>::max_len() {
-        panic!(
-            "Length {} is invalid: Block cannot be bigger than\
-             core::isize::MAX bytes ({} elements)",
-            len,
-            MemBlock::<E, L>::max_len()
-        );
+    if cfg!(not(feature = "mem-block-skip-size-check")) {
+        if let Err(err) = MemBlock::<E, L>::check_len(len) {
+            panic!("{}", err);
+        }
     }
 }
 
@@ -138,25 +229,78 @@ fn get_layout<E, L>(len: usize) -> Layout {
     }
 }
 
+/// Get the memory layout of a `MemBlock<E, L>` of length `len`, or `None` if the
+/// block would be too big or its layout is invalid for this platform
+fn try_get_layout<E, L>(len: usize) -> Option<Layout> {
+    let (size, align) = MemBlock::<E, L>::checked_memory_layout(len)?;
+    Some(unsafe { Layout::from_size_align_unchecked(size, align) })
+}
+
+/// Get the memory layout of a `MemBlock<E, L>` of length `len`, whose elements
+/// are aligned to at least `align` bytes
+fn get_layout_aligned<E, L>(len: usize, align: usize) -> Layout {
+    if cfg!(not(feature = "mem-block-skip-size-check")) {
+        if let Err(err) = MemBlock::<E, L>::check_len_aligned(len, align) {
+            panic!("{}", err);
+        }
+    }
+    let (size, align) = MemBlock::<E, L>::memory_layout_aligned(len, align);
+    match Layout::from_size_align(size, align) {
+        Ok(layout) => layout,
+        Err(err) => {
+            panic!(
+                "MemBlock of length {} is invalid for this platform;\n\
+                 it has (size, align) = ({}, {}), causing error\n{:#?}",
+                len, size, align, err
+            );
+        }
+    }
+}
+
+/// Make sure that an allocated pointer isn't null
+fn check_ptr<E, L>(ptr: MutMB<E, L>) -> MutMB<E, L> {
+    if cfg!(feature = "mem-block-skip-ptr-check") {
+        ptr
+    } else {
+        assert!(
+            !ptr.is_null(),
+            "Allocated a null pointer.\
+             You may be out of memory.",
+        );
+        ptr
+    }
+}
+
 unsafe impl<E, L> BaseArrayPtr<E, L> for *mut MemBlock<E, L> {
     unsafe fn alloc(len: usize) -> Self {
         let layout = get_layout::<E, L>(len);
-        let ptr = allocate(layout, Global);
-        if cfg!(feature = "mem-block-skip-ptr-check") {
-            ptr
+        check_ptr(allocate(layout, Global))
+    }
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        let ptr: Self = allocate(try_get_layout::<E, L>(len)?, Global);
+        if ptr.is_null() {
+            None
         } else {
-            assert!(
-                !ptr.is_null(),
-                "Allocated a null pointer.\
-                 You may be out of memory.",
-            );
-            ptr
+            Some(ptr)
         }
     }
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        let layout = get_layout::<E, L>(len);
+        check_ptr(allocate_zeroed(layout, Global))
+    }
     unsafe fn dealloc(&mut self, len: usize) {
         let layout = get_layout::<E, L>(len);
         deallocate(*self, layout, Global);
     }
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        let old_layout = get_layout::<E, L>(old_len);
+        let new_layout = get_layout::<E, L>(new_len);
+        if old_layout.align() == new_layout.align() {
+            *self = check_ptr(reallocate(*self, old_layout, new_layout.size(), Global));
+        } else {
+            realloc_by_copy(self, old_len, new_len);
+        }
+    }
     unsafe fn from_ptr(ptr: *mut u8) -> Self {
         ptr as *mut MemBlock<E, L>
     }
@@ -182,9 +326,20 @@ unsafe impl<E, L> BaseArrayPtr<E, L> for NonNull<MemBlock<E, L>> {
     unsafe fn alloc(len: usize) -> Self {
         NonNull::new_unchecked(MutMB::alloc(len))
     }
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        MutMB::try_alloc(len).map(|ptr| NonNull::new_unchecked(ptr))
+    }
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        NonNull::new_unchecked(MutMB::alloc_zeroed(len))
+    }
     unsafe fn dealloc(&mut self, len: usize) {
         self.clone().as_ptr().dealloc(len)
     }
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        let mut ptr = self.as_ptr();
+        ptr.realloc(old_len, new_len);
+        *self = NonNull::new_unchecked(ptr);
+    }
     unsafe fn from_ptr(ptr: *mut u8) -> Self {
         NonNull::new_unchecked(MutMB::from_ptr(ptr))
     }
@@ -206,9 +361,20 @@ unsafe impl<E, L> BaseArrayPtr<E, L> for AtomicPtr<MemBlock<E, L>> {
     unsafe fn alloc(len: usize) -> Self {
         AtomicPtr::new(MutMB::alloc(len))
     }
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        MutMB::try_alloc(len).map(AtomicPtr::new)
+    }
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        AtomicPtr::new(MutMB::alloc_zeroed(len))
+    }
     unsafe fn dealloc(&mut self, len: usize) {
         self.load(Ordering::Acquire).dealloc(len)
     }
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        let mut ptr = self.load(Ordering::Acquire);
+        ptr.realloc(old_len, new_len);
+        self.store(ptr, Ordering::Release);
+    }
     unsafe fn from_ptr(ptr: *mut u8) -> Self {
         AtomicPtr::new(MutMB::from_ptr(ptr))
     }
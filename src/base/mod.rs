@@ -1,5 +1,39 @@
 /*!
 Defines the `BaseArray` struct.
+
+`BaseArray` is the low-level building block that the arrays in this crate are
+built on; it handles allocation, initialization, and destruction of a block,
+but leaves keeping track of the length to its user. Custom array types can be
+built on top of it without working with `MemBlock` directly:
+
+```rust
+use heaparray::base::BaseArray;
+
+/// Array that stores its own length as its label.
+struct MyArray<E>(BaseArray<E, usize>);
+
+impl<E> MyArray<E> {
+    fn new(len: usize, mut func: impl FnMut(usize) -> E) -> Self {
+        Self(BaseArray::new(len, len, |_, i| func(i)))
+    }
+    fn len(&self) -> usize {
+        *self.0.get_label()
+    }
+    fn iter(&self) -> core::slice::Iter<'_, E> {
+        unsafe { self.0.iter(self.len()) }
+    }
+}
+
+impl<E> Drop for MyArray<E> {
+    fn drop(&mut self) {
+        let len = self.len();
+        unsafe { self.0.drop(len) };
+    }
+}
+
+let array = MyArray::new(4, |i| i * 2);
+assert_eq!(array.iter().sum::<usize>(), 12);
+```
 */
 
 mod alloc_utils;
@@ -7,6 +41,7 @@ mod base;
 mod mem_block;
 mod traits;
 
+pub(crate) use base::InitGuard;
 pub use base::{BaseArray, BaseArrayIter};
 pub use mem_block::MemBlock;
 pub use traits::*;
@@ -1,4 +1,25 @@
 //! Defines `BaseArrayPtr`, the interface `BaseArray` uses when defining methods.
+use super::mem_block::MemBlock;
+use crate::error::CapacityError;
+
+/// Resizes `array` by allocating a new block, moving the label and the first
+/// `min(old_len, new_len)` elements into it, and deallocating the old block.
+///
+/// # Safety
+/// Same as `BaseArrayPtr::realloc`.
+pub(crate) unsafe fn realloc_by_copy<E, L, P>(array: &mut P, old_len: usize, new_len: usize)
+where
+    P: BaseArrayPtr<E, L>,
+{
+    let new_array = P::alloc(new_len);
+    core::ptr::copy_nonoverlapping(array.lbl_ptr(), new_array.lbl_ptr(), 1);
+    let len = core::cmp::min(old_len, new_len);
+    if len > 0 {
+        core::ptr::copy_nonoverlapping(array.elem_ptr(0), new_array.elem_ptr(0), len);
+    }
+    array.dealloc(old_len);
+    core::ptr::write(array, new_array);
+}
 
 /// Trait representing an unsafe reference to an array.
 ///
@@ -57,10 +78,58 @@ pub unsafe trait BaseArrayPtr<E, L>: Sized {
     /// initializing it
     unsafe fn alloc(len: usize) -> Self;
 
+    /// Allocate the memory necessary for a new instance of `len` elements, without
+    /// initializing it, returning `None` instead of panicking if the allocation
+    /// fails
+    ///
+    /// # Safety
+    /// Same as `alloc`. The default implementation calls `alloc`, and so never
+    /// returns `None`.
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        Some(Self::alloc(len))
+    }
+
+    /// Returns an error if a block of `len` elements would be too big for this
+    /// pointer type to allocate
+    ///
+    /// The default implementation checks against `MemBlock::<E, L>::max_len()`,
+    /// so pointer types that store more than the label in the block, or that pad
+    /// its layout, need to override it.
+    fn check_len(len: usize) -> Result<(), CapacityError> {
+        MemBlock::<E, L>::check_len(len)
+    }
+
+    /// Allocate the memory necessary for a new instance of `len` elements, with
+    /// all of the memory for elements set to zero
+    ///
+    /// # Safety
+    /// Same as `alloc`; additionally, the default implementation writes zeroes
+    /// through `elem_ptr`, so implementors must uphold its invariants.
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        let array = Self::alloc(len);
+        if len > 0 {
+            core::ptr::write_bytes(array.elem_ptr(0), 0, len);
+        }
+        array
+    }
+
     /// Deallocate the memory for an instance of `len` elements, without running
     /// destructors
     unsafe fn dealloc(&mut self, len: usize);
 
+    /// Resize the memory for an instance of `old_len` elements so that it can
+    /// hold `new_len` elements, preserving the label and the first
+    /// `min(old_len, new_len)` elements
+    ///
+    /// # Safety
+    /// `self` must have been allocated for `old_len` elements, and elements at
+    /// index `new_len` or above must have already been dropped or moved out by
+    /// the caller. The default implementation allocates a new block, moves the
+    /// label and elements into it, and deallocates the old block.
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        realloc_by_copy(self, old_len, new_len)
+    }
+
     /// Creates a new reference of this type without doing any checks.
     ///
     /// # Safety
@@ -0,0 +1,48 @@
+//! Error types returned by fallible operations on arrays.
+use core::fmt;
+
+/// Error returned when the memory block for an array can't be allocated, either
+/// because the allocator failed or because the block's layout is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Memory allocation failed")
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for AllocError {}
+
+/// Error returned when the requested length of a memory block is greater than
+/// the maximum length that a block holding its types can have, i.e.
+/// `MemBlock::max_len()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError {
+    /// The requested length.
+    pub len: usize,
+    /// The maximum length of the block.
+    pub max_len: usize,
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "Length {} is invalid: Block cannot be bigger than\
+             core::isize::MAX bytes ({} elements)",
+            self.len, self.max_len
+        )
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for CapacityError {}
+
+/// A block whose length is too large can't be allocated either.
+impl From<CapacityError> for AllocError {
+    fn from(_: CapacityError) -> Self {
+        AllocError
+    }
+}
@@ -0,0 +1,154 @@
+//! Contains definition for `PartialArray`, which builds an array whose
+//! elements can be initialized in any order.
+
+use super::p_types::FatPtrArray;
+use crate::prelude::*;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+#[cfg(feature = "no-std")]
+use crate::alloc::vec::Vec;
+
+/// Array whose elements are being initialized one at a time, that keeps track
+/// of which of them have been initialized so far.
+///
+/// Elements can be initialized in order with `push`, or at any index with
+/// `set`; once every element has been initialized, `finish` returns the array.
+/// Dropping an unfinished builder only drops the elements that were
+/// initialized, along with the label.
+///
+/// ```rust
+/// # use heaparray::impls::FatPtrArray;
+/// # use heaparray::*;
+/// let mut builder = FatPtrArray::builder("label", 3);
+/// builder.set(2, 'c');
+/// builder.push('a').unwrap();
+/// builder.push('b').unwrap();
+/// let array = builder.finish().unwrap();
+/// assert_eq!(array.as_slice(), ['a', 'b', 'c']);
+/// ```
+pub struct PartialArray<E, L = ()> {
+    array: FatPtrArray<MaybeUninit<E>, L>,
+    initialized: Vec<bool>,
+    count: usize,
+    next: usize,
+}
+
+impl<E, L> FatPtrArray<E, L> {
+    /// Returns a builder for an array of length `len` with the given label, whose
+    /// elements are all uninitialized.
+    pub fn builder(label: L, len: usize) -> PartialArray<E, L> {
+        let mut initialized = Vec::new();
+        initialized.resize(len, false);
+        PartialArray {
+            array: FatPtrArray::with_label_uninit(label, len),
+            initialized,
+            count: 0,
+            next: 0,
+        }
+    }
+}
+
+impl<E, L> PartialArray<E, L> {
+    /// Returns the number of elements that have been initialized so far.
+    pub fn initialized(&self) -> usize {
+        self.count
+    }
+
+    /// Returns whether or not the element at index `idx` has been initialized.
+    ///
+    /// # Panics
+    /// Panics if `idx >= len`.
+    pub fn is_set(&self, idx: usize) -> bool {
+        self.check_index(idx);
+        self.initialized[idx]
+    }
+
+    /// Initializes the element at index `idx` to `elem`. If it was already
+    /// initialized, the old element is dropped.
+    ///
+    /// # Panics
+    /// Panics if `idx >= len`.
+    pub fn set(&mut self, idx: usize, elem: E) {
+        self.check_index(idx);
+        let old = mem::replace(&mut self.array[idx], MaybeUninit::new(elem));
+        if self.initialized[idx] {
+            // The slot already holds the new element, so if dropping the old one
+            // panics, the builder is still consistent.
+            mem::drop(unsafe { old.assume_init() });
+        } else {
+            self.initialized[idx] = true;
+            self.count += 1;
+            self.skip_initialized();
+        }
+    }
+
+    /// Initializes the first element that hasn't been initialized yet to `elem`,
+    /// or returns `elem` back if every element has been initialized.
+    pub fn push(&mut self, elem: E) -> Result<(), E> {
+        if self.next == self.len() {
+            return Err(elem);
+        }
+        self.set(self.next, elem);
+        Ok(())
+    }
+
+    /// Returns the finished array, or returns this builder back if some of its
+    /// elements haven't been initialized yet.
+    pub fn finish(self) -> Result<FatPtrArray<E, L>, Self> {
+        if self.count != self.len() {
+            return Err(self);
+        }
+        let mut this = mem::ManuallyDrop::new(self);
+        unsafe {
+            ptr::drop_in_place(&mut this.initialized);
+            Ok(ptr::read(&this.array).assume_init())
+        }
+    }
+
+    fn check_index(&self, idx: usize) {
+        let len = self.len();
+        assert!(
+            idx < len,
+            "Index {} is out of bounds for array of length {}",
+            idx,
+            len
+        );
+    }
+
+    fn skip_initialized(&mut self) {
+        while self.next < self.len() && self.initialized[self.next] {
+            self.next += 1;
+        }
+    }
+}
+
+impl<E, L> Container for PartialArray<E, L> {
+    fn len(&self) -> usize {
+        self.array.len()
+    }
+}
+
+impl<E, L> Drop for PartialArray<E, L> {
+    fn drop(&mut self) {
+        for (elem, &initialized) in self.array.iter_mut().zip(&self.initialized) {
+            if initialized {
+                unsafe { ptr::drop_in_place(elem.as_mut_ptr()) };
+            }
+        }
+    }
+}
+
+impl<E, L> fmt::Debug for PartialArray<E, L>
+where
+    L: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("PartialArray")
+            .field("label", self.array.get_label())
+            .field("len", &self.len())
+            .field("initialized", &self.count)
+            .finish()
+    }
+}
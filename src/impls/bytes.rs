@@ -0,0 +1,65 @@
+//! Implements conversions between arrays of plain-old-data elements and bytes
+//! with `bytemuck`, when the `bytemuck` feature is enabled.
+
+use super::generic::*;
+use crate::prelude::*;
+use bytemuck::Pod;
+use core::ptr;
+
+impl<E, L, P> SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: Pod,
+{
+    /// Returns the elements of this array as a slice of bytes, of length
+    /// `len * size_of::<E>()`. The label isn't included.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new(2, |i| i as u16 + 1);
+    /// assert_eq!(array.as_bytes().len(), 4);
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(self.as_slice())
+    }
+
+    /// Returns the elements of this array as a mutable slice of bytes, of
+    /// length `len * size_of::<E>()`. The label isn't included.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        bytemuck::cast_slice_mut(self.as_slice_mut())
+    }
+}
+
+impl<E, P> SafeArray<E, (), P>
+where
+    P: SafeArrayPtr<E, ()>,
+    E: Pod,
+{
+    /// Creates a new array whose elements are copied from `bytes`.
+    ///
+    /// `bytes` doesn't need to be aligned for `E`.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::<u32>::from_bytes(&7u32.to_ne_bytes());
+    /// assert_eq!(array.as_slice(), [7]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the length of `bytes` isn't a multiple of `size_of::<E>()`, or
+    /// if `E` is zero-sized.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let size = mem::size_of::<E>();
+        assert!(
+            bytes.len().checked_rem(size) == Some(0),
+            "Byte slice of length {} can't be split into elements of size {}",
+            bytes.len(),
+            size
+        );
+        unsafe {
+            let mut out = Self::new_lazy((), bytes.len() / size);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), out.as_mut_ptr() as *mut u8, bytes.len());
+            out
+        }
+    }
+}
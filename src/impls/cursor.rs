@@ -0,0 +1,112 @@
+//! Contains definition for `Cursor`, which reads and writes the elements of an
+//! array in order.
+
+use super::generic::*;
+use super::p_types::FatArrayPtr;
+use crate::prelude::*;
+
+/// Position in a mutably borrowed array, for filling or consuming its elements
+/// in order without keeping track of indices by hand.
+///
+/// Each `read` or `write` acts on the element at the cursor's position, and then
+/// moves the cursor forward by one; once the cursor reaches the end of the
+/// array, `read` returns `None` and `write` returns `false`.
+///
+/// ```rust
+/// # use heaparray::impls::Cursor;
+/// # use heaparray::*;
+/// let mut array = HeapArray::new(3, |_| 0u8);
+/// let mut cursor = Cursor::new(&mut array);
+/// assert!(cursor.write(1));
+/// assert!(cursor.write(2));
+/// assert_eq!(cursor.remaining(), 1);
+/// cursor.seek(0);
+/// assert_eq!(cursor.read(), Some(&1));
+/// ```
+pub struct Cursor<'a, E, L, P = FatArrayPtr<E, L>>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    array: &'a mut SafeArray<E, L, P>,
+    pos: usize,
+}
+
+impl<'a, E, L, P> Cursor<'a, E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    /// Creates a new cursor at the start of `array`.
+    pub fn new(array: &'a mut SafeArray<E, L, P>) -> Self {
+        Self { array, pos: 0 }
+    }
+
+    /// Returns the position of this cursor, i.e. the index of the element that
+    /// the next `read` or `write` acts on.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns the number of elements between this cursor and the end of the
+    /// array.
+    pub fn remaining(&self) -> usize {
+        self.array.len() - self.pos
+    }
+
+    /// Moves this cursor to `pos`. Seeking to the length of the array is allowed,
+    /// and leaves no elements remaining.
+    ///
+    /// # Panics
+    /// Panics if `pos` is greater than the length of the array.
+    pub fn seek(&mut self, pos: usize) {
+        let len = self.array.len();
+        assert!(
+            pos <= len,
+            "Index {} is out of bounds for array of length {}",
+            pos,
+            len
+        );
+        self.pos = pos;
+    }
+
+    /// Returns a reference to the element at this cursor's position and moves
+    /// past it, or returns `None` if the cursor is at the end of the array.
+    pub fn read(&mut self) -> Option<&E> {
+        let elem = self.array.as_slice().get(self.pos)?;
+        self.pos += 1;
+        Some(elem)
+    }
+
+    /// Overwrites the element at this cursor's position with `elem`, dropping the
+    /// old element, and moves past it. Returns `false`, dropping `elem`, if the
+    /// cursor is at the end of the array.
+    pub fn write(&mut self, elem: E) -> bool {
+        match self.array.as_slice_mut().get_mut(self.pos) {
+            Some(slot) => {
+                *slot = elem;
+                self.pos += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a reference to the array this cursor is over.
+    pub fn get_ref(&self) -> &SafeArray<E, L, P> {
+        self.array
+    }
+}
+
+impl<'a, E, L, P> fmt::Debug for Cursor<'a, E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: fmt::Debug,
+    L: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Cursor")
+            .field("array", &self.array)
+            .field("position", &self.pos)
+            .finish()
+    }
+}
@@ -1,5 +1,22 @@
+use super::p_types::FatPtrArray;
 use crate::base::*;
+use crate::error::{AllocError, CapacityError};
 use crate::prelude::*;
+use core::any::Any;
+use core::convert::TryFrom;
+use core::mem::MaybeUninit;
+use core::ops::{
+    Bound, RangeBounds, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive,
+};
+use core::ptr;
+#[cfg(target_has_atomic = "64")]
+use core::sync::atomic::{AtomicI64, AtomicU64};
+use core::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+
+#[cfg(feature = "no-std")]
+use crate::alloc::boxed::Box;
+#[cfg(feature = "no-std")]
+use crate::alloc::vec::Vec;
 
 /// Array pointer that also knows what its length is.
 ///
@@ -34,6 +51,1251 @@ where
     data: BaseArray<E, L, P>,
 }
 
+/// Iterator over the elements drained from an array by `SafeArray::drain`.
+///
+/// Elements that haven't been yielded by the time this iterator is dropped are
+/// dropped along with it.
+pub type Drain<E> = <FatPtrArray<E, ()> as IntoIterator>::IntoIter;
+
+/// Converts `range` into a pair of start and end indices into an array of length
+/// `len`.
+///
+/// # Panics
+/// Panics if the range is decreasing or extends past `len`, including when an
+/// inclusive end or exclusive start is `usize::MAX`.
+fn range_indices<R>(range: R, len: usize) -> (usize, usize)
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1).unwrap_or_else(|| {
+            panic!(
+                "Range starting after {} is out of bounds for array of length {}",
+                start, len
+            )
+        }),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1).unwrap_or_else(|| {
+            panic!(
+                "Range {}..={} is out of bounds for array of length {}",
+                start, end, len
+            )
+        }),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end && end <= len,
+        "Range {}..{} is out of bounds for array of length {}",
+        start,
+        end,
+        len
+    );
+    (start, end)
+}
+
+/// Guard for an array whose elements are being mapped into another array.
+///
+/// If dropped while mapping the element at index `mapped`, this drops the
+/// elements of `src` that come after it, the elements of `dest` that come before
+/// it, and the label of `dest`, and then deallocates both arrays. When mapping
+/// in place, `src` and `dest` point to the same block, which is only
+/// deallocated once.
+struct MapGuard<E, U, L, P, Q>
+where
+    P: BaseArrayPtr<E, L>,
+    Q: BaseArrayPtr<U, L>,
+{
+    src: BaseArray<E, L, P>,
+    dest: BaseArray<U, L, Q>,
+    len: usize,
+    mapped: usize,
+    in_place: bool,
+}
+
+impl<E, U, L, P, Q> Drop for MapGuard<E, U, L, P, Q>
+where
+    P: BaseArrayPtr<E, L>,
+    Q: BaseArrayPtr<U, L>,
+{
+    fn drop(&mut self) {
+        unsafe {
+            for i in self.mapped + 1..self.len {
+                ptr::drop_in_place(self.src.get_ptr_mut(i));
+            }
+            for i in 0..self.mapped {
+                ptr::drop_in_place(self.dest.get_ptr_mut(i));
+            }
+            ptr::drop_in_place(self.dest.get_label_mut());
+            self.dest.drop_lazy(self.len);
+            if !self.in_place {
+                self.src.drop_lazy(self.len);
+            }
+        }
+    }
+}
+
+/// Guard for an array whose length is being changed in place.
+///
+/// When dropped, this resizes the block of `array` from `alloc_len` to `len`
+/// elements if they differ, and then sets the array's length to `len`. Only the
+/// first `len` elements may be initialized by then, so if initializing or
+/// dropping an element panics, the array is left with exactly the elements that
+/// are still valid.
+struct ResizeGuard<'a, E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    array: &'a mut SafeArray<E, L, P>,
+    alloc_len: usize,
+    len: usize,
+}
+
+impl<'a, E, L, P> Drop for ResizeGuard<'a, E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    fn drop(&mut self) {
+        unsafe {
+            if self.alloc_len != self.len {
+                self.array.data.realloc(self.alloc_len, self.len);
+            }
+            self.array.data.as_ptr_mut().set_len(self.len);
+        }
+    }
+}
+
+impl<E, L, P> SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    /// Maps each element of this array into an array with pointer type `Q`,
+    /// consuming this array and carrying over its label.
+    ///
+    /// If `in_place` is true, the elements are overwritten in this array's
+    /// block, which must then have the same layout for elements of type `U`.
+    /// Otherwise, a new block is allocated.
+    ///
+    /// # Safety
+    /// If `in_place` is true, `U` must have the same size and alignment as `E`,
+    /// and `Q` must be the same kind of pointer as `P`.
+    pub(crate) unsafe fn map_into<U, Q, F>(self, in_place: bool, mut func: F) -> SafeArray<U, L, Q>
+    where
+        Q: SafeArrayPtr<U, L>,
+        F: FnMut(E) -> U,
+    {
+        let (src, len) = self.into_base();
+        let dest = if in_place {
+            BaseArray::from_ptr(src.as_ptr().cast::<U, L, Q>())
+        } else {
+            let dest = BaseArray::<U, L, Q>::alloc(len);
+            ptr::write(dest.as_ptr().lbl_ptr(), ptr::read(src.get_label()));
+            dest
+        };
+        let mut guard = MapGuard {
+            src,
+            dest,
+            len,
+            mapped: 0,
+            in_place,
+        };
+        while guard.mapped < len {
+            let elem = ptr::read(guard.src.get_ptr(guard.mapped));
+            let mapped = func(elem);
+            ptr::write(guard.dest.get_ptr_mut(guard.mapped), mapped);
+            guard.mapped += 1;
+        }
+        let src = ptr::read(&guard.src);
+        let dest = ptr::read(&guard.dest);
+        mem::forget(guard);
+        if !in_place {
+            let mut src = src;
+            src.drop_lazy(len);
+        }
+        SafeArray::from_base(dest, len)
+    }
+
+    /// Allocates an array of length `len` with an initialized label, but doesn't
+    /// initialize its elements.
+    pub(crate) unsafe fn new_lazy(label: L, len: usize) -> Self {
+        Self::from_base(BaseArray::new_lazy(label, len), len)
+    }
+
+    /// Constructs an array from a base array whose pointer already holds its
+    /// length, and whose elements have all been initialized. Unlike
+    /// `from_base`, this can be used in constant expressions.
+    pub(crate) const unsafe fn from_base_with_len(data: BaseArray<E, L, P>) -> Self {
+        Self { data }
+    }
+
+    /// Constructs an array from a base array whose first `len` elements have been
+    /// initialized.
+    pub(crate) unsafe fn from_base(mut data: BaseArray<E, L, P>, len: usize) -> Self {
+        data.as_ptr_mut().set_len(len);
+        Self { data }
+    }
+
+    /// Reinterprets this array as an array of elements of type `U`, with pointer
+    /// type `Q`, reusing its block.
+    ///
+    /// # Safety
+    /// `U` must have the same layout as `E`, the elements must be valid values
+    /// of `U`, and `Q` must be the same kind of pointer as `P`.
+    pub(crate) unsafe fn cast_elements<U, Q>(self) -> SafeArray<U, L, Q>
+    where
+        Q: SafeArrayPtr<U, L>,
+    {
+        let (data, len) = self.into_base();
+        SafeArray::from_base(data.cast_into::<U, Q>(), len)
+    }
+
+    /// Takes the underlying base array out of this array without running any
+    /// destructors, returning it alongside its length.
+    pub(crate) fn into_base(self) -> (BaseArray<E, L, P>, usize) {
+        let len = self.len();
+        let data = unsafe { ptr::read(&self.data) };
+        mem::forget(self);
+        (data, len)
+    }
+
+    /// Shrinks the block of this array to `new_len` elements in place, and sets
+    /// its length to `new_len`, without dropping any elements.
+    ///
+    /// # Safety
+    /// `new_len` must not be greater than the length of this array, and the
+    /// elements past `new_len` must already have been dropped or moved out.
+    pub(crate) unsafe fn shrink_block(&mut self, new_len: usize) {
+        let len = self.len();
+        self.data.realloc(len, new_len);
+        self.data.as_ptr_mut().set_len(new_len);
+    }
+
+    /// Moves `len` elements starting at `start` out of `source` into a new,
+    /// unlabelled array.
+    ///
+    /// # Safety
+    /// The moved elements must be initialized, and are logically uninitialized
+    /// in `source` after this call.
+    unsafe fn move_from(
+        source: &BaseArray<E, L, P>,
+        start: usize,
+        len: usize,
+    ) -> FatPtrArray<E, ()> {
+        let mut out = FatPtrArray::new_lazy((), len);
+        if len > 0 {
+            ptr::copy_nonoverlapping(source.get_ptr(start), out.data.get_ptr_mut(0), len);
+        }
+        out
+    }
+
+    /// Drops the label of `data` and deallocates it, without running destructors
+    /// on its elements.
+    ///
+    /// # Safety
+    /// All elements of `data` must have already been moved out or dropped.
+    unsafe fn dealloc_moved(mut data: BaseArray<E, L, P>, len: usize) {
+        ptr::drop_in_place(data.get_label_mut());
+        data.drop_lazy(len);
+    }
+
+    /// Creates a new array of length `len`, computing its label with `label_fn`
+    /// and initializing each element with `elem_fn`.
+    ///
+    /// The label is computed once, right before the elements are initialized.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new_with(3, || "squares", |i| i * i);
+    /// assert_eq!(*array.get_label(), "squares");
+    /// assert_eq!(array.as_slice(), [0, 1, 4]);
+    /// ```
+    pub fn new_with<G, F>(len: usize, label_fn: G, mut elem_fn: F) -> Self
+    where
+        G: FnOnce() -> L,
+        F: FnMut(usize) -> E,
+    {
+        Self::with_label(label_fn(), len, |_, i| elem_fn(i))
+    }
+
+    /// Creates a new array of length `len` with the given label, initializing
+    /// each element with `func`, which may fail.
+    ///
+    /// If `func` returns an error, the elements that were already initialized
+    /// and the label are dropped, the array is deallocated, and the error is
+    /// returned.
+    pub fn try_with_label<F, Er>(label: L, len: usize, mut func: F) -> Result<Self, Er>
+    where
+        F: FnMut(&mut L, usize) -> Result<E, Er>,
+    {
+        let mut data = unsafe { BaseArray::new_lazy(label, len) };
+        let mut guard = InitGuard {
+            array: &mut data,
+            len,
+            initialized: 0,
+        };
+        for i in 0..len {
+            let elem = func(guard.array.get_label_mut(), i)?;
+            unsafe { ptr::write(guard.array.get_ptr_mut(i), elem) };
+            guard.initialized += 1;
+        }
+        mem::forget(guard);
+        Ok(unsafe { Self::from_base(data, len) })
+    }
+
+    /// Consumes this array, dropping its elements and deallocating its block, and
+    /// returns its label.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label(0, 10, |evens, i| {
+    ///     *evens += (i % 2 == 0) as usize;
+    ///     i
+    /// });
+    /// assert_eq!(array.into_label(), 5);
+    /// ```
+    pub fn into_label(self) -> L {
+        let (mut data, len) = self.into_base();
+        unsafe {
+            let label = ptr::read(data.get_label());
+            ptr::drop_in_place(data.as_slice_mut(len));
+            data.drop_lazy(len);
+            label
+        }
+    }
+
+    /// Consumes this array, deallocating its block without running the
+    /// destructors of its elements. The label is still dropped.
+    ///
+    /// Unlike `mem::forget`, which leaks the whole block, this only leaks what the
+    /// elements own; it's meant for when every element has already been moved
+    /// out, e.g. through the pointer returned by `as_mut_ptr`.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::new(2, |i| i.to_string());
+    /// let elements = unsafe { core::ptr::read(array.as_mut_ptr() as *const [String; 2]) };
+    /// array.forget_contents();
+    /// assert_eq!(elements, ["0", "1"]);
+    /// ```
+    pub fn forget_contents(self) {
+        let (data, len) = self.into_base();
+        unsafe { Self::dealloc_moved(data, len) };
+    }
+
+    /// Replaces the label of this array with `label`, returning the old label.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::with_label("old", 3, |_, i| i);
+    /// assert_eq!(array.set_label("new"), "old");
+    /// assert_eq!(*array.get_label(), "new");
+    /// ```
+    pub fn set_label(&mut self, label: L) -> L {
+        mem::replace(self.get_label_mut(), label)
+    }
+
+    /// Clones this array, copying all of its elements at once instead of cloning
+    /// them one at a time like `clone` does.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label("label", 1000, |_, i| i as u64);
+    /// let copy = array.clone_copy();
+    /// assert_eq!(copy, array);
+    /// ```
+    pub fn clone_copy(&self) -> Self
+    where
+        E: Copy,
+        L: Clone,
+    {
+        let len = self.len();
+        unsafe { Self::from_base(self.data.clone_copy(len), len) }
+    }
+
+    /// Clones the elements of this array into a new array labelled with `label`.
+    ///
+    /// Unlike `clone`, this doesn't require the label to implement `Clone`.
+    pub fn clone_with_label(&self, label: L) -> Self
+    where
+        E: Clone,
+    {
+        let elements = self.as_slice();
+        Self::with_label(label, elements.len(), |_, i| elements[i].clone())
+    }
+
+    /// Clones this array into `target`.
+    ///
+    /// When both arrays have the same length, the label and elements are
+    /// cloned into the existing block of `target` using `clone_from`, without
+    /// allocating. Otherwise, `target` is replaced with a new clone.
+    pub fn clone_into(&self, target: &mut Self)
+    where
+        E: Clone,
+        L: Clone,
+    {
+        target.clone_from(self);
+    }
+
+    /// Consumes this array without deallocating it, returning a mutable slice
+    /// into its elements that lives for the rest of the program.
+    ///
+    /// The label can't be accessed anymore, and neither it nor the elements
+    /// will ever be dropped.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let table: &'static mut [usize] = HeapArray::new(3, |i| i * i).leak();
+    /// table[0] = 10;
+    /// assert_eq!(table, [10, 1, 4]);
+    /// ```
+    pub fn leak<'a>(self) -> &'a mut [E]
+    where
+        E: 'a,
+    {
+        let (mut data, len) = self.into_base();
+        unsafe { &mut *ptr::slice_from_raw_parts_mut(data.get_ptr_mut(0), len) }
+    }
+
+    /// Splits this array into two unlabelled arrays at the index `mid`,
+    /// consuming it in the process.
+    ///
+    /// The first array contains the elements in `[0, mid)`, and the second
+    /// contains the elements in `[mid, len)`. Elements are moved into two newly
+    /// allocated blocks, and the label of this array is dropped.
+    ///
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at(self, mid: usize) -> (FatPtrArray<E, ()>, FatPtrArray<E, ()>) {
+        assert!(
+            mid <= self.len(),
+            "Index {} is out of bounds for array of length {}",
+            mid,
+            self.len()
+        );
+        let (data, len) = self.into_base();
+        unsafe {
+            let left = Self::move_from(&data, 0, mid);
+            let right = Self::move_from(&data, mid, len - mid);
+            Self::dealloc_moved(data, len);
+            (left, right)
+        }
+    }
+
+    /// Joins this array and `other` into a single array, consuming both in the
+    /// process.
+    ///
+    /// The elements of `self` come first, followed by the elements of `other`.
+    /// Elements are moved into a newly allocated block, and the labels of both
+    /// arrays are dropped; the new array's label is `L::default()`.
+    pub fn concat(self, other: Self) -> Self
+    where
+        L: Default,
+    {
+        let (first, first_len) = self.into_base();
+        let (second, second_len) = other.into_base();
+        unsafe {
+            let mut out = Self::new_lazy(L::default(), first_len + second_len);
+            let dest = out.data.get_ptr_mut(0);
+            if first_len > 0 {
+                ptr::copy_nonoverlapping(first.get_ptr(0), dest, first_len);
+            }
+            if second_len > 0 {
+                ptr::copy_nonoverlapping(second.get_ptr(0), dest.add(first_len), second_len);
+            }
+            Self::dealloc_moved(first, first_len);
+            Self::dealloc_moved(second, second_len);
+            out
+        }
+    }
+
+    /// Shortens this array to its first `new_len` elements, consuming it in the
+    /// process.
+    ///
+    /// The remaining elements and the label are moved into a newly allocated
+    /// block of the correct size, and the trailing elements are dropped. Returns
+    /// this array unchanged if `new_len >= len`.
+    pub fn truncate(self, new_len: usize) -> Self {
+        if new_len >= self.len() {
+            return self;
+        }
+        let (mut data, len) = self.into_base();
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                data.get_ptr_mut(new_len),
+                len - new_len,
+            ));
+            let mut out = Self::new_lazy(ptr::read(data.get_label()), new_len);
+            if new_len > 0 {
+                ptr::copy_nonoverlapping(data.get_ptr(0), out.data.get_ptr_mut(0), new_len);
+            }
+            data.drop_lazy(len);
+            out
+        }
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, consuming this
+    /// array in the process.
+    ///
+    /// Kept elements are moved, in order, into a newly allocated block of the
+    /// exact size needed, along with the label; the other elements are dropped.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label("evens", 6, |_, i| i);
+    /// let array = array.retain(|&i| i % 2 == 0);
+    /// assert_eq!(array.as_slice(), [0, 2, 4]);
+    /// assert_eq!(*array.get_label(), "evens");
+    /// ```
+    pub fn retain<F>(self, mut f: F) -> Self
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.filter_into(|_, elem| f(elem))
+    }
+
+    /// Removes consecutive repeated elements, consuming this array in the
+    /// process.
+    ///
+    /// Like `Vec::dedup`, only the first of each run of equal elements is kept.
+    /// Kept elements are moved into a newly allocated block of the exact size
+    /// needed, along with the label; the other elements are dropped.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new(7, |i| [1, 1, 2, 3, 3, 3, 1][i]);
+    /// assert_eq!(array.dedup().as_slice(), [1, 2, 3, 1]);
+    /// ```
+    pub fn dedup(self) -> Self
+    where
+        E: PartialEq,
+    {
+        self.filter_into(|kept, elem| kept.last() != Some(elem))
+    }
+
+    /// Moves the elements for which `keep` returns `true` into a new array with
+    /// this array's label, dropping the others. `keep` is passed the elements
+    /// kept so far along with the element being considered.
+    fn filter_into<F>(self, mut keep: F) -> Self
+    where
+        F: FnMut(&[E], &E) -> bool,
+    {
+        let (mut data, len) = self.into_base();
+        let (label, elements) = unsafe {
+            let elements = Self::move_from(&data, 0, len);
+            let label = ptr::read(data.get_label());
+            data.drop_lazy(len);
+            (label, elements)
+        };
+        let mut kept = Vec::with_capacity(len);
+        for elem in elements {
+            if keep(&kept, &elem) {
+                kept.push(elem);
+            }
+        }
+        unsafe {
+            let mut out = Self::new_lazy(label, kept.len());
+            if !kept.is_empty() {
+                ptr::copy_nonoverlapping(kept.as_ptr(), out.data.get_ptr_mut(0), kept.len());
+            }
+            kept.set_len(0);
+            out
+        }
+    }
+
+    /// Removes the elements in `range` from this array, consuming it in the
+    /// process.
+    ///
+    /// Since arrays can't change length, this returns a new array holding the
+    /// elements outside of `range` and this array's label, alongside an iterator
+    /// that yields the removed elements by value. Both are backed by newly
+    /// allocated blocks.
+    ///
+    /// # Panics
+    /// Panics if `range` is decreasing or extends past the end of the array.
+    pub fn drain<R>(self, range: R) -> (Self, Drain<E>)
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = range_indices(range, self.len());
+        let (mut data, len) = self.into_base();
+        unsafe {
+            let drained = Self::move_from(&data, start, end - start);
+            let mut rest = Self::new_lazy(ptr::read(data.get_label()), len - (end - start));
+            let dest = rest.data.get_ptr_mut(0);
+            if start > 0 {
+                ptr::copy_nonoverlapping(data.get_ptr(0), dest, start);
+            }
+            if end < len {
+                ptr::copy_nonoverlapping(data.get_ptr(end), dest.add(start), len - end);
+            }
+            data.drop_lazy(len);
+            (rest, drained.into_iter())
+        }
+    }
+
+    /// Copies the elements of `src` into this array using a single memory copy.
+    ///
+    /// # Panics
+    /// Panics if `src` and this array have different lengths.
+    pub fn copy_from_slice(&mut self, src: &[E])
+    where
+        E: Copy,
+    {
+        let len = self.len();
+        assert!(
+            src.len() == len,
+            "Source slice of length {} doesn't match array of length {}",
+            src.len(),
+            len
+        );
+        if len > 0 {
+            unsafe { ptr::copy_nonoverlapping(src.as_ptr(), self.data.get_ptr_mut(0), len) };
+        }
+    }
+
+    /// Clones the elements of `src` into this array, dropping the elements that
+    /// they replace.
+    ///
+    /// If cloning an element panics, the elements that were already replaced
+    /// keep their new values and the rest keep their old ones.
+    ///
+    /// # Panics
+    /// Panics if `src` and this array have different lengths.
+    pub fn clone_from_slice(&mut self, src: &[E])
+    where
+        E: Clone,
+    {
+        let len = self.len();
+        assert!(
+            src.len() == len,
+            "Source slice of length {} doesn't match array of length {}",
+            src.len(),
+            len
+        );
+        for (dest, elem) in self.iter_mut().zip(src) {
+            dest.clone_from(elem);
+        }
+    }
+
+    /// Swaps the elements of this array with the elements of `other`.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::new(3, |i| i);
+    /// let mut vec = vec![5, 6, 7];
+    /// array.swap_with_slice(&mut vec);
+    /// assert_eq!(array.as_slice(), [5, 6, 7]);
+    /// assert_eq!(vec, [0, 1, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `other` and this array have different lengths.
+    pub fn swap_with_slice(&mut self, other: &mut [E]) {
+        let len = self.len();
+        assert!(
+            other.len() == len,
+            "Slice of length {} doesn't match array of length {}",
+            other.len(),
+            len
+        );
+        if len > 0 {
+            unsafe { ptr::swap_nonoverlapping(self.data.get_ptr_mut(0), other.as_mut_ptr(), len) };
+        }
+    }
+
+    /// Swaps the element at index `i` of this array with the element at index `j`
+    /// of `other`, which can have a different label and pointer type.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// # use heaparray::impls::ThinPtrArray;
+    /// let mut left = HeapArray::new(3, |i| i);
+    /// let mut right = ThinPtrArray::with_label("label", 2, |_, i| i + 10);
+    /// left.swap_with(0, &mut right, 1);
+    /// assert_eq!(left.as_slice(), [11, 1, 2]);
+    /// assert_eq!(right.as_slice(), [10, 0]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `i` is out of bounds for this array, or `j` is out of bounds
+    /// for `other`.
+    pub fn swap_with<L2, Q>(&mut self, i: usize, other: &mut SafeArray<E, L2, Q>, j: usize)
+    where
+        Q: SafeArrayPtr<E, L2>,
+    {
+        for &(idx, len) in &[(i, self.len()), (j, other.len())] {
+            assert!(
+                idx < len,
+                "Index {} is out of bounds for array of length {}",
+                idx,
+                len
+            );
+        }
+        unsafe { ptr::swap(self.data.get_ptr_mut(i), other.data.get_ptr_mut(j)) };
+    }
+
+    /// Copies the elements in `src` to the part of this array starting at index
+    /// `dest`. The two parts of the array are allowed to overlap.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::new(5, |i| i);
+    /// array.copy_within(1..4, 0);
+    /// assert_eq!(array.as_slice(), [1, 2, 3, 3, 4]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `src` is out of bounds, or if the part of the array starting at
+    /// `dest` is shorter than `src`.
+    pub fn copy_within<R>(&mut self, src: R, dest: usize)
+    where
+        E: Copy,
+        R: RangeBounds<usize>,
+    {
+        let len = self.len();
+        let (start, end) = range_indices(src, len);
+        let count = end - start;
+        assert!(
+            dest <= len - count,
+            "Destination {}..{} is out of bounds for array of length {}",
+            dest,
+            dest.saturating_add(count),
+            len
+        );
+        if count > 0 {
+            unsafe {
+                let src = self.data.get_ptr(start);
+                ptr::copy(src, self.data.get_ptr_mut(dest), count);
+            }
+        }
+    }
+
+    /// Returns two slices into this array, split at the index `mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at_slice(&self, mid: usize) -> (&[E], &[E]) {
+        self.as_slice().split_at(mid)
+    }
+
+    /// Returns mutable references to the elements at each of the given indices,
+    /// or `None` if any index is out-of-bounds or if any two indices are equal.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::new(4, |i| i);
+    /// if let Some([a, b]) = array.get_many_mut([0, 3]) {
+    ///     core::mem::swap(a, b);
+    /// }
+    /// assert!(array.as_slice() == [3, 1, 2, 0]);
+    /// ```
+    pub fn get_many_mut<const N: usize>(&mut self, indices: [usize; N]) -> Option<[&mut E; N]> {
+        let len = self.len();
+        for (i, &idx) in indices.iter().enumerate() {
+            if idx >= len || indices[..i].contains(&idx) {
+                return None;
+            }
+        }
+        let elems = self.data.get_ptr_mut(0);
+        Some(indices.map(|idx| unsafe { &mut *elems.add(idx) }))
+    }
+
+    /// Returns a reference to the element at index `idx`, without checking that
+    /// it's in bounds.
+    ///
+    /// The index is only checked with a `debug_assert!`, like
+    /// `slice::get_unchecked`.
+    ///
+    /// # Safety
+    /// Callers must ensure that `idx < len`; calling this with an out-of-bounds
+    /// index is undefined behavior, even if the returned reference is unused.
+    pub unsafe fn get_unchecked(&self, idx: usize) -> &E {
+        debug_assert!(
+            idx < self.len(),
+            "Index {} is out of bounds for array of length {}",
+            idx,
+            self.len()
+        );
+        self.data.get(idx)
+    }
+
+    /// Same as `get_unchecked`, but returns a mutable reference.
+    ///
+    /// # Safety
+    /// Callers must ensure that `idx < len`; calling this with an out-of-bounds
+    /// index is undefined behavior, even if the returned reference is unused.
+    pub unsafe fn get_unchecked_mut(&mut self, idx: usize) -> &mut E {
+        debug_assert!(
+            idx < self.len(),
+            "Index {} is out of bounds for array of length {}",
+            idx,
+            self.len()
+        );
+        self.data.get_mut(idx)
+    }
+
+    /// Returns whether or not this array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether or not this array contains an element equal to `x`.
+    pub fn contains(&self, x: &E) -> bool
+    where
+        E: PartialEq,
+    {
+        self.as_slice().contains(x)
+    }
+
+    /// Returns the index of the first element for which `pred` returns `true`,
+    /// or `None` if there isn't one.
+    pub fn position<F>(&self, pred: F) -> Option<usize>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.iter().position(pred)
+    }
+
+    /// Returns the index of the last element for which `pred` returns `true`,
+    /// or `None` if there isn't one.
+    pub fn rposition<F>(&self, pred: F) -> Option<usize>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.iter().rposition(pred)
+    }
+
+    /// Replaces every element of this array with its default value, keeping the
+    /// same block and label.
+    ///
+    /// Each new element is constructed before the old one is dropped, so if
+    /// `E::default` panics, the array is left with some elements reset and the
+    /// rest untouched.
+    pub fn clear_to_default(&mut self)
+    where
+        E: Default,
+    {
+        for elem in self.iter_mut() {
+            *elem = E::default();
+        }
+    }
+
+    /// Replaces the element at index `idx` with its default value, returning the
+    /// element that was there.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::new(3, |i| i.to_string());
+    /// assert_eq!(array.take(1), "1");
+    /// assert_eq!(array[1], "");
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `idx >= len`.
+    pub fn take(&mut self, idx: usize) -> E
+    where
+        E: Default,
+    {
+        let len = self.len();
+        assert!(
+            idx < len,
+            "Index {} is out of bounds for array of length {}",
+            idx,
+            len
+        );
+        mem::take(&mut self.as_slice_mut()[idx])
+    }
+
+    /// Returns a reference to the first element of this array, or `None` if
+    /// it's empty.
+    pub fn first(&self) -> Option<&E> {
+        self.as_slice().first()
+    }
+
+    /// Returns a mutable reference to the first element of this array, or `None`
+    /// if it's empty.
+    pub fn first_mut(&mut self) -> Option<&mut E> {
+        self.as_slice_mut().first_mut()
+    }
+
+    /// Returns a reference to the last element of this array, or `None` if
+    /// it's empty.
+    pub fn last(&self) -> Option<&E> {
+        self.as_slice().last()
+    }
+
+    /// Returns a mutable reference to the last element of this array, or `None`
+    /// if it's empty.
+    pub fn last_mut(&mut self) -> Option<&mut E> {
+        self.as_slice_mut().last_mut()
+    }
+
+    /// Returns the first element of this array and a slice of the rest of the
+    /// elements, or `None` if it's empty.
+    pub fn split_first(&self) -> Option<(&E, &[E])> {
+        self.as_slice().split_first()
+    }
+
+    /// Same as `split_first`, but with mutable references.
+    pub fn split_first_mut(&mut self) -> Option<(&mut E, &mut [E])> {
+        self.as_slice_mut().split_first_mut()
+    }
+
+    /// Returns the last element of this array and a slice of the rest of the
+    /// elements, or `None` if it's empty.
+    pub fn split_last(&self) -> Option<(&E, &[E])> {
+        self.as_slice().split_last()
+    }
+
+    /// Same as `split_last`, but with mutable references.
+    pub fn split_last_mut(&mut self) -> Option<(&mut E, &mut [E])> {
+        self.as_slice_mut().split_last_mut()
+    }
+
+    /// Returns a raw pointer to the first element of this array.
+    ///
+    /// If the array is empty, the pointer is dangling but non-null and aligned,
+    /// like `NonNull::dangling`. The pointer is only valid for as long as this
+    /// array is, and must not be written through.
+    pub fn as_ptr(&self) -> *const E {
+        if self.is_empty() {
+            ptr::NonNull::dangling().as_ptr()
+        } else {
+            self.data.get_ptr(0)
+        }
+    }
+
+    /// Returns a mutable raw pointer to the first element of this array.
+    ///
+    /// If the array is empty, the pointer is dangling but non-null and aligned,
+    /// like `NonNull::dangling`. The pointer is only valid for as long as this
+    /// array is.
+    pub fn as_mut_ptr(&mut self) -> *mut E {
+        if self.is_empty() {
+            ptr::NonNull::dangling().as_ptr()
+        } else {
+            self.data.get_ptr_mut(0)
+        }
+    }
+
+    /// Returns an iterator over references to the elements of this array.
+    pub fn iter(&self) -> core::slice::Iter<'_, E> {
+        self.as_slice().iter()
+    }
+
+    /// Returns the label alongside the element at index `idx`.
+    ///
+    /// # Panics
+    /// Panics if `idx >= len`.
+    pub fn entry(&self, idx: usize) -> (&L, &E) {
+        match self.get(idx) {
+            Some(elem) => (self.get_label(), elem),
+            None => panic!(
+                "Index {} is out of bounds for array of length {}",
+                idx,
+                self.len()
+            ),
+        }
+    }
+
+    /// Returns an iterator over references to the elements of this array, each
+    /// paired with a reference to the label.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label(10, 3, |_, i| i);
+    /// let sums: Vec<usize> = array.iter_with_label().map(|(l, e)| l + e).collect();
+    /// assert_eq!(sums, [10, 11, 12]);
+    /// ```
+    pub fn iter_with_label(&self) -> impl DoubleEndedIterator<Item = (&L, &E)> + ExactSizeIterator {
+        let label = self.get_label();
+        self.iter().map(move |elem| (label, elem))
+    }
+
+    /// Returns an iterator that moves the elements out of this array, each
+    /// paired with a clone of the label, consuming the array in the process.
+    ///
+    /// The label is cloned once for every element yielded; the array's own
+    /// label is dropped along with the iterator.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label("group", 2, |_, i| i);
+    /// let pairs: Vec<(&str, usize)> = array.into_iter_with_label().collect();
+    /// assert_eq!(pairs, [("group", 0), ("group", 1)]);
+    /// ```
+    pub fn into_iter_with_label(self) -> impl DoubleEndedIterator<Item = (L, E)> + ExactSizeIterator
+    where
+        L: Clone,
+    {
+        let label = self.get_label().clone();
+        self.into_iter().map(move |elem| (label.clone(), elem))
+    }
+
+    /// Returns a reference to the label along with a mutable slice of the
+    /// elements, so that the label can be read while the elements are changed.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let mut array = HeapArray::with_label(10, 3, |_, i| i);
+    /// let (offset, elements) = array.label_and_slice_mut();
+    /// for elem in elements {
+    ///     *elem += offset;
+    /// }
+    /// assert_eq!(array.as_slice(), [10, 11, 12]);
+    /// ```
+    pub fn label_and_slice_mut(&mut self) -> (&L, &mut [E]) {
+        let len = self.len();
+        // The label and the elements are in separate parts of the block, so the
+        // two references don't alias.
+        let label = self.data.as_ptr().lbl_ptr();
+        unsafe { (&*label, self.data.as_slice_mut(len)) }
+    }
+
+    /// Returns an iterator over references to the elements of this array, from
+    /// last to first.
+    pub fn iter_rev(&self) -> core::iter::Rev<core::slice::Iter<'_, E>> {
+        self.iter().rev()
+    }
+
+    /// Returns an iterator over mutable references to the elements of this array.
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, E> {
+        self.as_slice_mut().iter_mut()
+    }
+
+    /// Returns an iterator over non-overlapping slices of `size` elements of this
+    /// array. The last slice is shorter if `size` doesn't divide the length.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> core::slice::Chunks<'_, E> {
+        self.as_slice().chunks(size)
+    }
+
+    /// Same as `chunks`, but with mutable slices.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn chunks_mut(&mut self, size: usize) -> core::slice::ChunksMut<'_, E> {
+        self.as_slice_mut().chunks_mut(size)
+    }
+
+    /// Returns an iterator over non-overlapping slices of `size` elements of this
+    /// array, starting at the end. The last slice is shorter if `size` doesn't
+    /// divide the length.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn rchunks(&self, size: usize) -> core::slice::RChunks<'_, E> {
+        self.as_slice().rchunks(size)
+    }
+
+    /// Returns an iterator over non-overlapping slices of exactly `size`
+    /// elements of this array. If `size` doesn't divide the length, the
+    /// leftover elements are available through the iterator's `remainder`.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn chunks_exact(&self, size: usize) -> core::slice::ChunksExact<'_, E> {
+        self.as_slice().chunks_exact(size)
+    }
+
+    /// Same as `chunks_exact`, but with mutable slices. The leftover elements
+    /// are available through the iterator's `into_remainder`.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn chunks_exact_mut(&mut self, size: usize) -> core::slice::ChunksExactMut<'_, E> {
+        self.as_slice_mut().chunks_exact_mut(size)
+    }
+
+    /// Returns an iterator over all overlapping slices of `size` elements of
+    /// this array. Yields nothing if the array is shorter than `size`.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    pub fn windows(&self, size: usize) -> core::slice::Windows<'_, E> {
+        self.as_slice().windows(size)
+    }
+
+    /// Returns an iterator over the slices of this array separated by elements
+    /// that match `pred`. The matched elements aren't contained in the slices.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new(5, |i| b"ab,cd"[i]);
+    /// let pieces: Vec<&[u8]> = array.split(|&b| b == b',').collect();
+    /// assert_eq!(pieces, [&b"ab"[..], &b"cd"[..]]);
+    /// ```
+    pub fn split<F>(&self, pred: F) -> core::slice::Split<'_, E, F>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.as_slice().split(pred)
+    }
+
+    /// Same as `split`, but starting at the end of the array and working
+    /// backwards.
+    pub fn rsplit<F>(&self, pred: F) -> core::slice::RSplit<'_, E, F>
+    where
+        F: FnMut(&E) -> bool,
+    {
+        self.as_slice().rsplit(pred)
+    }
+}
+
+impl<E, L, P> SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: Zeroable,
+{
+    /// Creates a new array of length `len` whose elements are all zero, with the
+    /// label initialized to the provided value.
+    ///
+    /// Elements aren't initialized individually; instead, the memory for the
+    /// array is requested already zeroed from the allocator.
+    pub fn with_label_zeroed(label: L, len: usize) -> Self {
+        unsafe {
+            let mut data = BaseArray::alloc_zeroed(len);
+            ptr::write(data.get_label_mut(), label);
+            Self::from_base(data, len)
+        }
+    }
+}
+
+impl<E, L, P> SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: ZeroDefault,
+{
+    /// Creates a new array of length `len` with the given label, like
+    /// `DefaultLabelledArray::with_len`, but by asking the allocator for zeroed
+    /// memory instead of calling `E::default()` for each element.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::<u64, _>::with_len_zeroed("label", 100);
+    /// assert_eq!(array, HeapArray::with_len("label", 100));
+    /// ```
+    pub fn with_len_zeroed(label: L, len: usize) -> Self {
+        Self::with_label_zeroed(label, len)
+    }
+}
+
+impl<E, P> SafeArray<E, (), P>
+where
+    P: SafeArrayPtr<E, ()>,
+    E: Zeroable,
+{
+    /// Creates a new array of length `len` whose elements are all zero.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::<u64>::new_zeroed(100);
+    /// assert!(array.iter().all(|&x| x == 0));
+    /// ```
+    pub fn new_zeroed(len: usize) -> Self {
+        Self::with_label_zeroed((), len)
+    }
+}
+
+impl<E, L, P> SafeArray<MaybeUninit<E>, L, P>
+where
+    P: SafeArrayPtr<MaybeUninit<E>, L>,
+{
+    /// Creates a new array of length `len` with the given label, whose elements
+    /// are left uninitialized.
+    ///
+    /// Once every element has been initialized, `assume_init` converts this
+    /// into an array of `E` without reallocating.
+    pub fn with_label_uninit(label: L, len: usize) -> Self {
+        unsafe { Self::new_lazy(label, len) }
+    }
+}
+
+impl<E, P> SafeArray<MaybeUninit<E>, (), P>
+where
+    P: SafeArrayPtr<MaybeUninit<E>, ()>,
+{
+    /// Creates a new array of length `len` whose elements are left uninitialized.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// # use core::mem::MaybeUninit;
+    /// let mut array = HeapArray::<MaybeUninit<u32>>::new_uninit(4);
+    /// for (i, elem) in array.iter_mut().enumerate() {
+    ///     *elem = MaybeUninit::new(i as u32);
+    /// }
+    /// let array = unsafe { array.assume_init() };
+    /// assert_eq!(array.as_slice(), [0, 1, 2, 3]);
+    /// ```
+    pub fn new_uninit(len: usize) -> Self {
+        Self::with_label_uninit((), len)
+    }
+}
+
+impl<E, P> SafeArray<E, (), P>
+where
+    P: SafeArrayPtr<E, ()>,
+    E: Clone,
+{
+    /// Creates a new array of length `len` whose elements are all clones of
+    /// `value`.
+    ///
+    /// `value` itself is moved into the last slot, so only `len - 1` clones are
+    /// made; if `len == 0`, `value` is dropped.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::repeat(String::from("hello"), 3);
+    /// assert!(array.iter().all(|s| s == "hello"));
+    /// ```
+    pub fn repeat(value: E, len: usize) -> Self {
+        let mut data = unsafe { BaseArray::new_lazy((), len) };
+        if len > 0 {
+            let mut guard = InitGuard {
+                array: &mut data,
+                len,
+                initialized: 0,
+            };
+            for i in 0..len - 1 {
+                unsafe { ptr::write(guard.array.get_ptr_mut(i), value.clone()) };
+                guard.initialized += 1;
+            }
+            unsafe { ptr::write(guard.array.get_ptr_mut(len - 1), value) };
+            mem::forget(guard);
+        }
+        unsafe { Self::from_base(data, len) }
+    }
+}
+
+impl<L, P> SafeArray<Box<dyn Any>, L, P>
+where
+    P: SafeArrayPtr<Box<dyn Any>, L>,
+{
+    /// Returns an iterator that tries to downcast each element of this array to
+    /// `T`, yielding `None` for the elements of a different type.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// use core::any::Any;
+    /// let array: HeapArray<Box<dyn Any>> = HeapArray::from([
+    ///     Box::new(1u32) as Box<dyn Any>,
+    ///     Box::new("two"),
+    /// ]);
+    /// let numbers: Vec<Option<&u32>> = array.iter_downcast().collect();
+    /// assert_eq!(numbers, [Some(&1), None]);
+    /// ```
+    pub fn iter_downcast<T: 'static>(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Option<&T>> + ExactSizeIterator {
+        self.iter().map(|elem| elem.downcast_ref())
+    }
+}
+
 impl<E, L, P> Container for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
@@ -97,7 +1359,7 @@ where
         self.data.get_label()
     }
     unsafe fn get_unchecked(&self, idx: usize) -> &E {
-        self.data.get(idx)
+        SafeArray::get_unchecked(self, idx)
     }
 }
 
@@ -109,7 +1371,20 @@ where
         self.data.get_label_mut()
     }
     unsafe fn get_mut_unchecked(&mut self, idx: usize) -> &mut E {
-        self.data.get_mut(idx)
+        self.get_unchecked_mut(idx)
+    }
+}
+
+unsafe impl<E, L, P> SplitDropArray<E, L> for SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+{
+    unsafe fn drop_elements(&mut self) {
+        ptr::drop_in_place(self.as_slice_mut());
+    }
+    unsafe fn dealloc_lazy(&mut self) {
+        let len = self.len();
+        self.data.drop_lazy(len);
     }
 }
 
@@ -125,6 +1400,91 @@ where
     }
 }
 
+impl<E, P> TryFrom<&[E]> for SafeArray<E, (), P>
+where
+    E: Clone,
+    P: SafeArrayPtr<E, ()>,
+{
+    type Error = AllocError;
+
+    /// Clones the elements of `slice` into a new array, returning an error if
+    /// the array can't be allocated.
+    ///
+    /// If cloning an element panics, the elements that were already cloned are
+    /// dropped and the array is deallocated.
+    fn try_from(slice: &[E]) -> Result<Self, AllocError> {
+        let len = slice.len();
+        let mut data = unsafe { BaseArray::try_alloc(len) }.ok_or(AllocError)?;
+        let mut guard = InitGuard {
+            array: &mut data,
+            len,
+            initialized: 0,
+        };
+        for (i, elem) in slice.iter().enumerate() {
+            unsafe { ptr::write(guard.array.get_ptr_mut(i), elem.clone()) };
+            guard.initialized += 1;
+        }
+        mem::forget(guard);
+        Ok(unsafe { Self::from_base(data, len) })
+    }
+}
+
+/// Moves the elements of a fixed-size array into a new array.
+impl<E, P, const N: usize> From<[E; N]> for SafeArray<E, (), P>
+where
+    P: SafeArrayPtr<E, ()>,
+{
+    fn from(elements: [E; N]) -> Self {
+        unsafe {
+            let mut out = Self::new_lazy((), N);
+            ptr::write(out.data.get_ptr_mut(0) as *mut [E; N], elements);
+            out
+        }
+    }
+}
+
+/// Moves the elements of an array into a fixed-size array, or returns the
+/// array unchanged if its length isn't `N`.
+///
+/// ```rust
+/// # use heaparray::*;
+/// use core::convert::TryFrom;
+/// let array = HeapArray::new(3, |i| i);
+/// let array = <[usize; 2]>::try_from(array).unwrap_err();
+/// assert_eq!(<[usize; 3]>::try_from(array), Ok([0, 1, 2]));
+/// ```
+impl<E, P, const N: usize> TryFrom<SafeArray<E, (), P>> for [E; N]
+where
+    P: SafeArrayPtr<E, ()>,
+{
+    type Error = SafeArray<E, (), P>;
+
+    fn try_from(array: SafeArray<E, (), P>) -> Result<Self, Self::Error> {
+        if array.len() != N {
+            return Err(array);
+        }
+        let (data, len) = array.into_base();
+        unsafe {
+            let elements = ptr::read(data.get_ptr(0) as *const [E; N]);
+            SafeArray::dealloc_moved(data, len);
+            Ok(elements)
+        }
+    }
+}
+
+/// Returns an empty array.
+///
+/// A block of length 0 only holds the label, so with a label of `()` this
+/// doesn't allocate at all.
+impl<E, P> Default for SafeArray<E, (), P>
+where
+    P: SafeArrayPtr<E, ()>,
+{
+    fn default() -> Self {
+        Self::new(0, |_| unreachable!())
+    }
+}
+
 impl<E, L, P> DefaultLabelledArray<E, L> for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
@@ -133,6 +1493,10 @@ where
     fn with_len(label: L, len: usize) -> Self {
         Self::with_label(label, len, |_, _| E::default())
     }
+    fn with_len_checked(label: L, len: usize) -> Result<Self, CapacityError> {
+        P::check_len(len)?;
+        Ok(Self::with_len(label, len))
+    }
 }
 
 impl<E, L, P> Clone for SafeArray<E, L, P>
@@ -142,36 +1506,84 @@ where
     L: Clone,
 {
     fn clone(&self) -> Self {
-        Self::with_label(self.get_label().clone(), self.len(), |_, i| self[i].clone())
+        let len = self.len();
+        unsafe { Self::from_base(self.data.clone(len), len) }
     }
+
+    /// Clones `source` into this array, reusing its block.
+    ///
+    /// The label and the elements both arrays have are cloned with
+    /// `clone_from`. If the lengths differ, the block is then resized with
+    /// `BaseArray::realloc`, after dropping the extra elements of this array or
+    /// before cloning the extra elements of `source` into it.
     fn clone_from(&mut self, source: &Self) {
-        if source.len() != self.len() {
-            *self = source.clone();
-        } else {
-            self.get_label_mut().clone_from(source.get_label());
-            for i in 0..source.len() {
-                self[i].clone_from(&source[i]);
+        let len = self.len();
+        let new_len = source.len();
+        self.get_label_mut().clone_from(source.get_label());
+        for (dest, elem) in self.iter_mut().zip(source.iter()) {
+            dest.clone_from(elem);
+        }
+        if new_len < len {
+            let guard = ResizeGuard {
+                array: self,
+                alloc_len: len,
+                len: new_len,
+            };
+            unsafe {
+                let excess = guard.array.data.get_ptr_mut(new_len);
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(excess, len - new_len));
+            }
+        } else if new_len > len {
+            unsafe { self.data.realloc(len, new_len) };
+            let mut guard = ResizeGuard {
+                array: self,
+                alloc_len: new_len,
+                len,
+            };
+            for i in len..new_len {
+                unsafe { ptr::write(guard.array.data.get_ptr_mut(i), source[i].clone()) };
+                guard.len += 1;
             }
         }
     }
 }
 
+/// Indexes into the array.
+///
+/// # Panics
+/// Panics if `idx >= len`, with the same message as indexing a slice. If the
+/// `no-asserts` feature is enabled, the index is instead only checked in debug
+/// builds, using `get_unchecked`; indexing out of bounds in a release build is
+/// then undefined behavior.
 impl<E, L, P> Index<usize> for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
 {
     type Output = E;
     fn index(&self, idx: usize) -> &E {
-        self.get(idx).unwrap()
+        #[cfg(not(feature = "no-asserts"))]
+        return &self.as_slice()[idx];
+        #[cfg(feature = "no-asserts")]
+        return unsafe { self.get_unchecked(idx) };
     }
 }
 
+/// Mutably indexes into the array.
+///
+/// # Panics
+/// Panics if `idx >= len`, with the same message as indexing a slice. If the
+/// `no-asserts` feature is enabled, the index is instead only checked in debug
+/// builds, using `get_unchecked_mut`; indexing out of bounds in a release build
+/// is then undefined behavior.
 impl<E, L, P> IndexMut<usize> for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
 {
     fn index_mut(&mut self, idx: usize) -> &mut E {
-        self.get_mut(idx).unwrap()
+        #[cfg(not(feature = "no-asserts"))]
+        return &mut self.as_slice_mut()[idx];
+        #[cfg(feature = "no-asserts")]
+        return unsafe { self.get_unchecked_mut(idx) };
     }
 }
 
@@ -210,25 +1622,42 @@ where
     }
 }
 
-impl<E, L, P> Index<Range<usize>> for SafeArray<E, L, P>
-where
-    P: SafeArrayPtr<E, L>,
-{
-    type Output = [E];
-    fn index(&self, idx: Range<usize>) -> &[E] {
-        &self.as_slice()[idx]
-    }
-}
+// Indexing by ranges panics with the array's length in the message, instead of
+// deferring to the slice's own bounds checks.
+macro_rules! impl_range_index {
+    ($($range:ty),*) => {$(
+        impl<E, L, P> Index<$range> for SafeArray<E, L, P>
+        where
+            P: SafeArrayPtr<E, L>,
+        {
+            type Output = [E];
+            fn index(&self, idx: $range) -> &[E] {
+                let (start, end) = range_indices(idx, self.len());
+                &self.as_slice()[start..end]
+            }
+        }
 
-impl<E, L, P> IndexMut<Range<usize>> for SafeArray<E, L, P>
-where
-    P: SafeArrayPtr<E, L>,
-{
-    fn index_mut(&mut self, idx: Range<usize>) -> &mut [E] {
-        &mut self.as_slice_mut()[idx]
-    }
+        impl<E, L, P> IndexMut<$range> for SafeArray<E, L, P>
+        where
+            P: SafeArrayPtr<E, L>,
+        {
+            fn index_mut(&mut self, idx: $range) -> &mut [E] {
+                let (start, end) = range_indices(idx, self.len());
+                &mut self.as_slice_mut()[start..end]
+            }
+        }
+    )*};
 }
 
+impl_range_index!(
+    Range<usize>,
+    RangeFrom<usize>,
+    RangeFull,
+    RangeInclusive<usize>,
+    RangeTo<usize>,
+    RangeToInclusive<usize>
+);
+
 impl<'a, E, L, P> IntoIterator for &'a SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
@@ -259,19 +1688,38 @@ where
     L: PartialEq<L2>,
 {
     fn eq(&self, other: &SafeArray<E2, L2, P2>) -> bool {
-        if self.get_label().eq(other.get_label()) {
-            for (e1, e2) in self.into_iter().zip(other.into_iter()) {
-                if e1.ne(e2) {
-                    return false;
-                }
+        self.get_label().eq(other.get_label()) && self.as_slice() == other.as_slice()
+    }
+}
+
+// Comparisons with slices and vectors only compare elements, since there's no
+// label on the other side to compare against.
+macro_rules! impl_slice_eq {
+    ($($slice:ty),*) => {$(
+        impl<E, L, P, E2> PartialEq<$slice> for SafeArray<E, L, P>
+        where
+            P: SafeArrayPtr<E, L>,
+            E: PartialEq<E2>,
+        {
+            fn eq(&self, other: &$slice) -> bool {
+                self.as_slice() == &other[..]
             }
-            true
-        } else {
-            false
         }
-    }
+
+        impl<E, L, P, E2> PartialEq<SafeArray<E, L, P>> for $slice
+        where
+            P: SafeArrayPtr<E, L>,
+            E2: PartialEq<E>,
+        {
+            fn eq(&self, other: &SafeArray<E, L, P>) -> bool {
+                &self[..] == other.as_slice()
+            }
+        }
+    )*};
 }
 
+impl_slice_eq!([E2], &'_ [E2], Vec<E2>);
+
 impl<E, L, P> Eq for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
@@ -280,6 +1728,107 @@ where
 {
 }
 
+// Atomics only need shared references to be updated, so these let threads
+// sharing a `&SafeArray` update its elements concurrently.
+macro_rules! impl_atomic_ops {
+    ($($atomic:ty => $int:ty),*) => {$(
+        impl<L, P> SafeArray<$atomic, L, P>
+        where
+            P: SafeArrayPtr<$atomic, L>,
+        {
+            /// Loads the value of the element at index `idx`.
+            ///
+            /// # Panics
+            /// Panics if `idx >= len`, or if `order` is `Release` or `AcqRel`.
+            pub fn load(&self, idx: usize, order: Ordering) -> $int {
+                self[idx].load(order)
+            }
+
+            /// Stores `val` into the element at index `idx`.
+            ///
+            /// # Panics
+            /// Panics if `idx >= len`, or if `order` is `Acquire` or `AcqRel`.
+            pub fn store(&self, idx: usize, val: $int, order: Ordering) {
+                self[idx].store(val, order)
+            }
+
+            /// Adds `val` to the element at index `idx`, returning its previous
+            /// value. Wraps around on overflow.
+            ///
+            /// # Panics
+            /// Panics if `idx >= len`.
+            pub fn fetch_add(&self, idx: usize, val: $int, order: Ordering) -> $int {
+                self[idx].fetch_add(val, order)
+            }
+
+            /// Subtracts `val` from the element at index `idx`, returning its
+            /// previous value. Wraps around on overflow.
+            ///
+            /// # Panics
+            /// Panics if `idx >= len`.
+            pub fn fetch_sub(&self, idx: usize, val: $int, order: Ordering) -> $int {
+                self[idx].fetch_sub(val, order)
+            }
+
+            /// Stores `new` into the element at index `idx` if its value is
+            /// `current`, returning the previous value in `Ok` if it was replaced,
+            /// or in `Err` otherwise.
+            ///
+            /// Only needs a shared reference, so threads can update the same
+            /// element without a lock by retrying until the exchange succeeds.
+            ///
+            /// # Panics
+            /// Panics if `idx >= len`, or if `failure` is `Release` or `AcqRel`.
+            pub fn cas(
+                &self,
+                idx: usize,
+                current: $int,
+                new: $int,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int, $int> {
+                self[idx].compare_exchange(current, new, success, failure)
+            }
+        }
+    )*};
+}
+
+impl_atomic_ops!(AtomicUsize => usize, AtomicIsize => isize);
+#[cfg(target_has_atomic = "64")]
+impl_atomic_ops!(AtomicU64 => u64, AtomicI64 => i64);
+
+/// Writes bytes into the array, using the label as the position of the next
+/// byte to write.
+///
+/// The array never grows; once the position reaches the end of the array,
+/// `write` returns `Ok(0)`, and so `write_all` fails with `ErrorKind::WriteZero`.
+///
+/// ```rust
+/// # use heaparray::*;
+/// use std::io::Write;
+/// let mut buffer = HeapArray::with_label(0, 8, |_, _| 0u8);
+/// buffer.write_all(b"hello").unwrap();
+/// assert_eq!(*buffer.get_label(), 5);
+/// assert!(buffer.write_all(b" world").is_err());
+/// ```
+#[cfg(not(feature = "no-std"))]
+impl<P> std::io::Write for SafeArray<u8, usize, P>
+where
+    P: SafeArrayPtr<u8, usize>,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let pos = (*self.get_label()).min(self.len());
+        let count = buf.len().min(self.len() - pos);
+        self.as_slice_mut()[pos..pos + count].copy_from_slice(&buf[..count]);
+        *self.get_label_mut() = pos + count;
+        Ok(count)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 impl<E, L, P> fmt::Debug for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L>,
@@ -296,6 +1845,9 @@ where
     }
 }
 
+// `BaseArray` is neither `Send` nor `Sync`, since it can't know whether it owns
+// its block. A `SafeArray` always does, the same way a `Box<[E]>` owns its
+// elements, so it's as thread-safe as its elements, label and pointer are.
 unsafe impl<E, L, P> Send for SafeArray<E, L, P>
 where
     P: SafeArrayPtr<E, L> + Send,
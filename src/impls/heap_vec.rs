@@ -0,0 +1,254 @@
+//! Contains definition for `HeapVec`, a growable array that keeps its label
+//! in the same block as its elements.
+
+use crate::base::*;
+use crate::prelude::*;
+use core::ptr;
+
+/// Growable array on the heap, with a label stored alongside its elements.
+///
+/// Keeps track of its length and capacity separately, like a `Vec`; when a push
+/// would exceed its capacity, the capacity is doubled by resizing the block
+/// with `BaseArray::realloc`.
+///
+/// ```rust
+/// # use heaparray::impls::HeapVec;
+/// # use heaparray::*;
+/// let mut vec = HeapVec::with_capacity(1);
+/// vec.push(1);
+/// vec.push(2);
+/// assert_eq!(vec.as_slice(), [1, 2]);
+/// assert_eq!(vec.pop(), Some(2));
+/// ```
+pub struct HeapVec<E, L = ()> {
+    data: BaseArray<E, L>,
+    len: usize,
+    cap: usize,
+}
+
+impl<E, L> HeapVec<E, L> {
+    /// Creates a new, empty vector with the given label, that can hold at least
+    /// `cap` elements before reallocating.
+    pub fn with_label_capacity(label: L, cap: usize) -> Self {
+        let cap = cap.max(1);
+        Self {
+            data: unsafe { BaseArray::new_lazy(label, cap) },
+            len: 0,
+            cap,
+        }
+    }
+
+    /// Returns whether or not this vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements this vector can hold before reallocating.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Returns the number of bytes allocated on the heap for this vector's memory
+    /// block, including the label, any padding, and unused capacity.
+    pub fn heap_bytes(&self) -> usize {
+        MemBlock::<E, L>::memory_layout(self.cap).0
+    }
+
+    /// Shrinks the block of this vector so that its capacity is its length,
+    /// freeing the unused capacity. Does nothing if the vector is already full.
+    ///
+    /// The capacity never goes below 1, so that pushing can keep doubling it.
+    pub fn shrink_to_fit(&mut self) {
+        let new_cap = self.len.max(1);
+        if new_cap < self.cap {
+            unsafe { self.data.realloc(self.cap, new_cap) };
+            self.cap = new_cap;
+        }
+    }
+
+    /// Makes sure this vector can hold at least `additional` more elements
+    /// before reallocating.
+    ///
+    /// If the capacity needs to grow, it's at least doubled, so that reserving
+    /// repeatedly takes amortized constant time per element.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len.checked_add(additional).expect("Capacity overflow");
+        if needed > self.cap {
+            let doubled = self.cap.checked_mul(2).expect("Capacity overflow");
+            let new_cap = needed.max(doubled);
+            unsafe { self.data.realloc(self.cap, new_cap) };
+            self.cap = new_cap;
+        }
+    }
+
+    /// Appends an element to the end of this vector, doubling its capacity if
+    /// it's full.
+    pub fn push(&mut self, elem: E) {
+        self.reserve(1);
+        unsafe { ptr::write(self.data.get_ptr_mut(self.len), elem) };
+        self.len += 1;
+    }
+
+    /// Clones the elements of `other` onto the end of this vector, reserving
+    /// space for all of them up front.
+    pub fn extend_from_slice(&mut self, other: &[E])
+    where
+        E: Clone,
+    {
+        self.extend(other.iter().cloned());
+    }
+
+    /// Removes the last element of this vector and returns it, or returns `None`
+    /// if the vector is empty.
+    pub fn pop(&mut self) -> Option<E> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            Some(unsafe { ptr::read(self.data.get_ptr(self.len)) })
+        }
+    }
+}
+
+impl<E> HeapVec<E, ()> {
+    /// Creates a new, empty vector that can hold at least `cap` elements before
+    /// reallocating.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_label_capacity((), cap)
+    }
+}
+
+impl<E, L> Container for HeapVec<E, L> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<E, L> Drop for HeapVec<E, L> {
+    fn drop(&mut self) {
+        unsafe { self.data.drop_range(self.cap, 0, self.len) };
+    }
+}
+
+/// Appends the items of an iterator, reserving space for as many items as the
+/// iterator's `size_hint` guarrantees up front.
+impl<E, L> Extend<E> for HeapVec<E, L> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+impl<E, L> CopyMap<usize, E> for HeapVec<E, L> {
+    fn get(&self, key: usize) -> Option<&E> {
+        self.as_slice().get(key)
+    }
+    fn get_mut(&mut self, key: usize) -> Option<&mut E> {
+        self.as_slice_mut().get_mut(key)
+    }
+    fn insert(&mut self, key: usize, value: E) -> Option<E> {
+        match self.get_mut(key) {
+            Some(slot) => Some(mem::replace(slot, value)),
+            None => None,
+        }
+    }
+}
+
+impl<E, L> LabelledArray<E, L> for HeapVec<E, L> {
+    fn with_label<F>(label: L, len: usize, mut func: F) -> Self
+    where
+        F: FnMut(&mut L, usize) -> E,
+    {
+        let mut vec = Self::with_label_capacity(label, len);
+        for i in 0..len {
+            let elem = func(vec.data.get_label_mut(), i);
+            vec.push(elem);
+        }
+        vec
+    }
+    fn get_label(&self) -> &L {
+        self.data.get_label()
+    }
+    unsafe fn get_unchecked(&self, idx: usize) -> &E {
+        self.data.get(idx)
+    }
+}
+
+impl<E, L> LabelledArrayMut<E, L> for HeapVec<E, L> {
+    fn get_label_mut(&mut self) -> &mut L {
+        self.data.get_label_mut()
+    }
+    unsafe fn get_mut_unchecked(&mut self, idx: usize) -> &mut E {
+        self.data.get_mut(idx)
+    }
+}
+
+impl<E> MakeArray<E> for HeapVec<E, ()> {
+    fn new<F>(len: usize, mut func: F) -> Self
+    where
+        F: FnMut(usize) -> E,
+    {
+        Self::with_label((), len, |_, idx| func(idx))
+    }
+}
+
+impl<E, L> SliceArray<E> for HeapVec<E, L> {
+    fn as_slice(&self) -> &[E] {
+        unsafe { self.data.as_slice(self.len) }
+    }
+}
+
+impl<E, L> SliceArrayMut<E> for HeapVec<E, L> {
+    fn as_slice_mut(&mut self) -> &mut [E] {
+        unsafe { self.data.as_slice_mut(self.len) }
+    }
+}
+
+impl<E, L> Index<usize> for HeapVec<E, L> {
+    type Output = E;
+    fn index(&self, idx: usize) -> &E {
+        &self.as_slice()[idx]
+    }
+}
+
+impl<E, L> IndexMut<usize> for HeapVec<E, L> {
+    fn index_mut(&mut self, idx: usize) -> &mut E {
+        &mut self.as_slice_mut()[idx]
+    }
+}
+
+impl<E, L> fmt::Debug for HeapVec<E, L>
+where
+    E: fmt::Debug,
+    L: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("HeapVec")
+            .field("label", &self.get_label())
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .field("elements", &self.as_slice())
+            .finish()
+    }
+}
+
+unsafe impl<E, L> Send for HeapVec<E, L>
+where
+    E: Send,
+    L: Send,
+{
+}
+
+unsafe impl<E, L> Sync for HeapVec<E, L>
+where
+    E: Sync,
+    L: Sync,
+{
+}
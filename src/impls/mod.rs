@@ -4,9 +4,19 @@ Implementations of safe APIs to the `BaseArray` struct.
 `BaseArray` is defined in [`heaparray::base`](../base/index.html).
 */
 
+mod builder;
+#[cfg(feature = "bytemuck")]
+mod bytes;
+mod cursor;
 mod generic;
+mod heap_vec;
 mod p_types;
+#[cfg(feature = "rayon")]
+mod par_iter;
 
 pub use crate::api_prelude::*;
+pub use builder::PartialArray;
+pub use cursor::Cursor;
 pub use generic::*;
-pub use p_types::{FatPtrArray, ThinPtrArray};
+pub use heap_vec::HeapVec;
+pub use p_types::{AlignedPtrArray, FatPtrArray, ThinPtrArray};
@@ -1,14 +1,33 @@
 use super::generic::*;
 use crate::base::*;
+use crate::error::CapacityError;
+use crate::prelude::*;
+use core::mem::MaybeUninit;
 use core::ptr::NonNull;
+use core::{mem, ptr};
+
+#[cfg(feature = "no-std")]
+use crate::alloc::boxed::Box;
 
 /// 1-word reference to an array on the heap that takes ownership of its contained
 /// data.
-pub type ThinPtrArray<E, L> = SafeArray<E, L, ThinArrayPtr<E, L>>;
+pub type ThinPtrArray<E, L = ()> = SafeArray<E, L, ThinArrayPtr<E, L>>;
 
 /// 2-word reference to an array on the heap that takes ownership of its contained
 /// data.
-pub type FatPtrArray<E, L> = SafeArray<E, L, FatArrayPtr<E, L>>;
+pub type FatPtrArray<E, L = ()> = SafeArray<E, L, FatArrayPtr<E, L>>;
+
+/// 2-word reference to an array on the heap whose elements are aligned to at
+/// least `ALIGN` bytes, which must be a power of two.
+///
+/// ```rust
+/// # use heaparray::impls::AlignedPtrArray;
+/// # use heaparray::*;
+/// let array = AlignedPtrArray::<u8, 64>::new(10, |i| i as u8);
+/// assert_eq!(array.as_slice().as_ptr() as usize % 64, 0);
+/// ```
+pub type AlignedPtrArray<E, const ALIGN: usize, L = ()> =
+    SafeArray<E, L, AlignedArrayPtr<E, L, ALIGN>>;
 
 struct LenLabel<L> {
     len: usize,
@@ -31,10 +50,28 @@ unsafe impl<E, L> BaseArrayPtr<E, L> for ThinArrayPtr<E, L> {
         }
     }
 
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        ThinPtr::try_alloc(len).map(|data| Self { data })
+    }
+
+    fn check_len(len: usize) -> Result<(), CapacityError> {
+        MemBlock::<E, LenLabel<L>>::check_len(len)
+    }
+
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        Self {
+            data: ThinPtr::alloc_zeroed(len),
+        }
+    }
+
     unsafe fn dealloc(&mut self, len: usize) {
         self.data.dealloc(len)
     }
 
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        self.data.realloc(old_len, new_len)
+    }
+
     unsafe fn from_ptr(ptr: *mut u8) -> Self {
         Self {
             data: ThinPtr::from_ptr(ptr),
@@ -67,6 +104,11 @@ unsafe impl<E, L> SafeArrayPtr<E, L> for ThinArrayPtr<E, L> {
     }
 }
 
+// Both pointer types own the block they point to, so they can be sent and
+// shared across threads whenever the block's contents can.
+unsafe impl<E: Send, L: Send> Send for ThinArrayPtr<E, L> {}
+unsafe impl<E: Sync, L: Sync> Sync for ThinArrayPtr<E, L> {}
+
 /// Fat pointer to a memory block, that implements the `BaseArrayPtr` and
 /// `SafeArrayPtr` traits.
 pub struct FatArrayPtr<E, L> {
@@ -82,10 +124,26 @@ unsafe impl<E, L> BaseArrayPtr<E, L> for FatArrayPtr<E, L> {
         }
     }
 
+    unsafe fn try_alloc(len: usize) -> Option<Self> {
+        NonNull::try_alloc(len).map(|data| Self { data, len })
+    }
+
+    unsafe fn alloc_zeroed(len: usize) -> Self {
+        Self {
+            data: NonNull::alloc_zeroed(len),
+            len,
+        }
+    }
+
     unsafe fn dealloc(&mut self, len: usize) {
         self.data.dealloc(len)
     }
 
+    unsafe fn realloc(&mut self, old_len: usize, new_len: usize) {
+        self.data.realloc(old_len, new_len);
+        self.len = new_len;
+    }
+
     unsafe fn from_ptr(ptr: *mut u8) -> Self {
         Self {
             data: NonNull::from_ptr(ptr),
@@ -110,6 +168,22 @@ unsafe impl<E, L> BaseArrayPtr<E, L> for FatArrayPtr<E, L> {
     }
 }
 
+impl<E> FatArrayPtr<E, ()> {
+    /// Returns a pointer to an unlabelled block of length 0, without allocating.
+    ///
+    /// Such a block takes up no memory, so `alloc` would return a dangling
+    /// pointer aligned to the elements anyways, and `dealloc` never frees it.
+    const fn empty() -> Self {
+        Self {
+            data: unsafe { NonNull::new_unchecked(mem::align_of::<E>() as *mut _) },
+            len: 0,
+        }
+    }
+}
+
+unsafe impl<E: Send, L: Send> Send for FatArrayPtr<E, L> {}
+unsafe impl<E: Sync, L: Sync> Sync for FatArrayPtr<E, L> {}
+
 unsafe impl<E, L> SafeArrayPtr<E, L> for FatArrayPtr<E, L> {
     fn set_len(&mut self, len: usize) {
         self.len = len;
@@ -118,3 +192,417 @@ unsafe impl<E, L> SafeArrayPtr<E, L> for FatArrayPtr<E, L> {
         self.len
     }
 }
+
+/// Fat pointer to a memory block whose elements are aligned to at least `ALIGN`
+/// bytes, that implements the `BaseArrayPtr` and `SafeArrayPtr` traits.
+pub struct AlignedArrayPtr<E, L, const ALIGN: usize> {
+    data: NonNull<MemBlock<E, L>>,
+    len: usize,
+}
+
+unsafe impl<E, L, const ALIGN: usize> BaseArrayPtr<E, L> for AlignedArrayPtr<E, L, ALIGN> {
+    unsafe fn alloc(len: usize) -> Self {
+        Self {
+            data: MemBlock::alloc_aligned(len, ALIGN),
+            len,
+        }
+    }
+
+    fn check_len(len: usize) -> Result<(), CapacityError> {
+        MemBlock::<E, L>::check_len_aligned(len, ALIGN)
+    }
+
+    unsafe fn dealloc(&mut self, len: usize) {
+        MemBlock::dealloc_aligned(self.data, len, ALIGN)
+    }
+
+    unsafe fn from_ptr(ptr: *mut u8) -> Self {
+        Self {
+            data: NonNull::from_ptr(ptr),
+            len: 0,
+        }
+    }
+
+    fn as_ptr(&self) -> *mut u8 {
+        self.data.as_ptr() as *mut u8
+    }
+
+    fn is_null(&self) -> bool {
+        self.data.is_null()
+    }
+
+    fn lbl_ptr(&self) -> *mut L {
+        self.data.lbl_ptr()
+    }
+
+    fn elem_ptr(&self, idx: usize) -> *mut E {
+        MemBlock::elem_ptr_aligned(self.data, idx, ALIGN)
+    }
+}
+
+unsafe impl<E, L, const ALIGN: usize> SafeArrayPtr<E, L> for AlignedArrayPtr<E, L, ALIGN> {
+    fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+    fn get_len(&self) -> usize {
+        self.len
+    }
+}
+
+unsafe impl<E: Send, L: Send, const ALIGN: usize> Send for AlignedArrayPtr<E, L, ALIGN> {}
+unsafe impl<E: Sync, L: Sync, const ALIGN: usize> Sync for AlignedArrayPtr<E, L, ALIGN> {}
+
+/// Returns whether or not `E` and `U` have the same size and alignment, in which
+/// case blocks holding either type have the same layout.
+fn same_layout<E, U>() -> bool {
+    mem::size_of::<E>() == mem::size_of::<U>() && mem::align_of::<E>() == mem::align_of::<U>()
+}
+
+/// Moves the contents of a block with a label of type `L1` into a block with a
+/// label of type `L2`, writing `label` into the new block.
+///
+/// If both blocks have the same memory layout for length `len`, the allocation
+/// is reused and only the label is written; otherwise a new block is allocated,
+/// the elements are moved into it, and the old block is deallocated.
+///
+/// # Safety
+/// The label of `block` must have already been moved out or dropped, and the
+/// first `len` elements of `block` must be initialized.
+unsafe fn relabel_block<E, L1, L2>(
+    mut block: NonNull<MemBlock<E, L1>>,
+    len: usize,
+    label: L2,
+) -> NonNull<MemBlock<E, L2>> {
+    if MemBlock::<E, L1>::memory_layout(len) == MemBlock::<E, L2>::memory_layout(len) {
+        let new_block = block.cast::<MemBlock<E, L2>>();
+        ptr::write(new_block.lbl_ptr(), label);
+        new_block
+    } else {
+        let new_block = NonNull::<MemBlock<E, L2>>::alloc(len);
+        ptr::write(new_block.lbl_ptr(), label);
+        if len > 0 {
+            ptr::copy_nonoverlapping(block.elem_ptr(0), new_block.elem_ptr(0), len);
+        }
+        block.dealloc(len);
+        new_block
+    }
+}
+
+impl<E, L, const ALIGN: usize> AlignedPtrArray<E, ALIGN, L> {
+    /// Returns the number of bytes allocated on the heap for this array's memory
+    /// block, including the label and any padding.
+    pub fn heap_bytes(&self) -> usize {
+        MemBlock::<E, L>::memory_layout_aligned(self.len(), ALIGN).0
+    }
+}
+
+impl<E, L> FatPtrArray<MaybeUninit<E>, L> {
+    /// Converts this array into an array of initialized elements, reusing its
+    /// block.
+    ///
+    /// # Safety
+    /// Every element of this array must have been initialized.
+    pub unsafe fn assume_init(self) -> FatPtrArray<E, L> {
+        self.cast_elements()
+    }
+}
+
+impl<E> FatPtrArray<E, ()> {
+    /// Returns an empty, unlabelled array, without allocating.
+    ///
+    /// Unlike `FatPtrArray::default`, this can be used in constant expressions,
+    /// e.g. to declare a sentinel value:
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// # use heaparray::impls::FatPtrArray;
+    /// static EMPTY: HeapArray<u8> = FatPtrArray::empty();
+    /// assert!(EMPTY.is_empty());
+    /// ```
+    pub const fn empty() -> Self {
+        unsafe { SafeArray::from_base_with_len(BaseArray::from_ptr(FatArrayPtr::empty())) }
+    }
+}
+
+// An unlabelled block has the same layout as a boxed slice of the same length:
+// its elements start at the beginning of the block, and a block of size 0 is
+// a dangling pointer that's never freed. Both are allocated with the global
+// allocator, so the conversions below hand the allocation over as-is.
+
+/// Takes ownership of the allocation of `boxed`, without copying or moving its
+/// elements.
+///
+/// ```rust
+/// # use heaparray::*;
+/// let boxed: Box<[u32]> = vec![1, 2, 3].into_boxed_slice();
+/// let array = HeapArray::from(boxed);
+/// assert_eq!(array.as_slice(), [1, 2, 3]);
+/// ```
+impl<E> From<Box<[E]>> for FatPtrArray<E, ()> {
+    fn from(boxed: Box<[E]>) -> Self {
+        let len = boxed.len();
+        unsafe {
+            let data = NonNull::new_unchecked(Box::into_raw(boxed) as *mut MemBlock<E, ()>);
+            FatPtrArray::from_base(BaseArray::from_ptr(FatArrayPtr { data, len }), len)
+        }
+    }
+}
+
+/// Hands the block of `array` over to a boxed slice, without copying or moving
+/// its elements.
+impl<E> From<FatPtrArray<E, ()>> for Box<[E]> {
+    fn from(array: FatPtrArray<E, ()>) -> Self {
+        let (data, len) = array.into_base();
+        unsafe {
+            let elements = data.as_ptr().data.as_ptr() as *mut E;
+            Box::from_raw(ptr::slice_from_raw_parts_mut(elements, len))
+        }
+    }
+}
+
+impl<E, L> FatPtrArray<E, L> {
+    /// Returns the number of bytes allocated on the heap for this array's memory
+    /// block, including the label and any padding.
+    pub fn heap_bytes(&self) -> usize {
+        MemBlock::<E, L>::memory_layout(self.len()).0
+    }
+
+    /// Creates a new array by calling `func` on each element of this array,
+    /// consuming it in the process. The label is carried over to the new array.
+    pub fn map<U, F>(self, func: F) -> FatPtrArray<U, L>
+    where
+        F: FnMut(E) -> U,
+    {
+        unsafe { self.map_into(false, func) }
+    }
+
+    /// Same as `map`, but reuses this array's block when `U` has the same size
+    /// and alignment as `E`, overwriting each element with its mapped value.
+    pub fn map_in_place<U, F>(self, func: F) -> FatPtrArray<U, L>
+    where
+        F: FnMut(E) -> U,
+    {
+        unsafe { self.map_into(same_layout::<E, U>(), func) }
+    }
+
+    /// Decomposes this array into a pointer to its memory block and its length,
+    /// without running any destructors.
+    ///
+    /// The caller becomes responsible for the label, the elements and the
+    /// block; the easiest way to clean them up is to turn them back into an
+    /// array with `from_raw_parts`.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new(4, |i| i);
+    /// let (block, len) = array.into_raw_parts();
+    /// let array = unsafe { HeapArray::from_raw_parts(block, len) };
+    /// assert_eq!(array.as_slice(), [0, 1, 2, 3]);
+    /// ```
+    pub fn into_raw_parts(self) -> (NonNull<MemBlock<E, L>>, usize) {
+        let (data, len) = self.into_base();
+        (data.as_ptr().data, len)
+    }
+
+    /// Creates an array from a pointer to a memory block and its length. The
+    /// array takes ownership of the block, and drops its contents and
+    /// deallocates it once it's dropped.
+    ///
+    /// # Safety
+    /// - `block` must have been allocated by this crate for a block of length
+    ///   `len`, with the same `E` and `L`, e.g. by calling `into_raw_parts` on
+    ///   a `FatPtrArray<E, L>` of length `len`.
+    /// - The label and all `len` elements of the block must be initialized.
+    /// - Nothing else may access or free the block after this call, since the
+    ///   returned array owns it.
+    pub unsafe fn from_raw_parts(block: NonNull<MemBlock<E, L>>, len: usize) -> Self {
+        let data = BaseArray::from_ptr(FatArrayPtr { data: block, len });
+        FatPtrArray::from_base(data, len)
+    }
+
+    /// Converts this array into a `ThinPtrArray`, which stores its length in
+    /// the memory block instead of in the pointer.
+    ///
+    /// The allocation is reused when the block's layout doesn't change after
+    /// adding the length to the block's header (e.g. when padding before the
+    /// elements leaves enough room for it). Otherwise, this reallocates once
+    /// and moves the label and elements into the new block.
+    pub fn into_thin(self) -> ThinPtrArray<E, L> {
+        let (data, len) = self.into_base();
+        unsafe {
+            let block = data.as_ptr().data;
+            let label = ptr::read(block.lbl_ptr());
+            let data = relabel_block(block, len, LenLabel { len, label });
+            ThinPtrArray::from_base(BaseArray::from_ptr(ThinArrayPtr { data }), len)
+        }
+    }
+
+    /// Converts the label of this array using `func`, consuming it in the
+    /// process.
+    ///
+    /// The allocation is reused when the new label doesn't change the block's
+    /// layout, i.e. when it fits in the space before the elements and doesn't
+    /// need a bigger alignment. Otherwise, this reallocates once and moves the
+    /// elements into the new block.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::new(3, |i| i);
+    /// let array = array.map_label(|()| "label");
+    /// assert_eq!(*array.get_label(), "label");
+    /// ```
+    pub fn map_label<L2, F>(self, func: F) -> FatPtrArray<E, L2>
+    where
+        F: FnOnce(L) -> L2,
+    {
+        let (data, len) = self.into_base();
+        unsafe {
+            let block = data.as_ptr().data;
+            let label = func(ptr::read(block.lbl_ptr()));
+            let data = relabel_block(block, len, label);
+            FatPtrArray::from_base(BaseArray::from_ptr(FatArrayPtr { data, len }), len)
+        }
+    }
+
+    /// Moves the label out of this array, returning it along with an unlabelled
+    /// array of the same elements.
+    ///
+    /// The label is stored at the start of the block, so like `map_label`, this
+    /// only reuses the allocation when removing the label doesn't change the
+    /// block's layout, e.g. when the label is zero-sized. Otherwise, this
+    /// reallocates once and moves the elements into the new block.
+    ///
+    /// ```rust
+    /// # use heaparray::*;
+    /// let array = HeapArray::with_label("label", 3, |_, i| i);
+    /// let (label, array) = array.take_label();
+    /// assert_eq!(label, "label");
+    /// assert_eq!(array.as_slice(), [0, 1, 2]);
+    /// ```
+    pub fn take_label(self) -> (L, FatPtrArray<E, ()>) {
+        let (data, len) = self.into_base();
+        unsafe {
+            let block = data.as_ptr().data;
+            let label = ptr::read(block.lbl_ptr());
+            let data = relabel_block(block, len, ());
+            let array = FatPtrArray::from_base(BaseArray::from_ptr(FatArrayPtr { data, len }), len);
+            (label, array)
+        }
+    }
+}
+
+impl<E, L> ThinPtrArray<MaybeUninit<E>, L> {
+    /// Converts this array into an array of initialized elements, reusing its
+    /// block.
+    ///
+    /// # Safety
+    /// Every element of this array must have been initialized.
+    pub unsafe fn assume_init(self) -> ThinPtrArray<E, L> {
+        self.cast_elements()
+    }
+}
+
+impl<E, L> ThinPtrArray<E, L> {
+    /// Returns the number of bytes allocated on the heap for this array's memory
+    /// block, including the label, the length stored alongside it, and any
+    /// padding.
+    pub fn heap_bytes(&self) -> usize {
+        MemBlock::<E, LenLabel<L>>::memory_layout(self.len()).0
+    }
+
+    /// Sets the length of this array to `new_len`, without dropping or
+    /// initializing any elements; for arrays that were allocated with more room
+    /// than they needed, e.g. with `new_uninit`, and then had a prefix of their
+    /// elements initialized manually.
+    ///
+    /// The length is stored in the block, and the block is freed using that
+    /// length, so shrinking the array also shrinks its block with
+    /// `BaseArray::realloc`, which allocators can usually do in place.
+    ///
+    /// ```rust
+    /// # use heaparray::impls::ThinPtrArray;
+    /// # use heaparray::*;
+    /// # use core::mem::MaybeUninit;
+    /// let mut array = ThinPtrArray::<MaybeUninit<u32>>::new_uninit(8);
+    /// array[0] = MaybeUninit::new(1);
+    /// array[1] = MaybeUninit::new(2);
+    /// let array = unsafe {
+    ///     array.set_len(2);
+    ///     array.assume_init()
+    /// };
+    /// assert_eq!(array.as_slice(), [1, 2]);
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `new_len` is greater than the length of this array.
+    ///
+    /// # Safety
+    /// The first `new_len` elements must be initialized. The elements past
+    /// `new_len` are neither dropped nor accessible afterwards.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        let len = self.len();
+        assert!(
+            new_len <= len,
+            "Length {} is greater than the allocated length {}",
+            new_len,
+            len
+        );
+        if new_len < len {
+            self.shrink_block(new_len);
+        }
+    }
+
+    /// Creates a new array by calling `func` on each element of this array,
+    /// consuming it in the process. The label is carried over to the new array.
+    pub fn map<U, F>(self, func: F) -> ThinPtrArray<U, L>
+    where
+        F: FnMut(E) -> U,
+    {
+        unsafe { self.map_into(false, func) }
+    }
+
+    /// Same as `map`, but reuses this array's block when `U` has the same size
+    /// and alignment as `E`, overwriting each element with its mapped value.
+    pub fn map_in_place<U, F>(self, func: F) -> ThinPtrArray<U, L>
+    where
+        F: FnMut(E) -> U,
+    {
+        unsafe { self.map_into(same_layout::<E, U>(), func) }
+    }
+
+    /// Converts this array into a `FatPtrArray`, which stores its length in
+    /// the pointer instead of in the memory block.
+    ///
+    /// The allocation is reused when the block's layout doesn't change after
+    /// removing the length from the block's header (e.g. when the elements are
+    /// aligned such that the header is padded to the same size either way).
+    /// Otherwise, this reallocates once and moves the label and elements into
+    /// the new block.
+    pub fn into_fat(self) -> FatPtrArray<E, L> {
+        let (data, len) = self.into_base();
+        unsafe {
+            let block = data.as_ptr().data;
+            let label = ptr::read(block.lbl_ptr()).label;
+            let data = relabel_block(block, len, label);
+            FatPtrArray::from_base(BaseArray::from_ptr(FatArrayPtr { data, len }), len)
+        }
+    }
+
+    /// Converts the label of this array using `func`, consuming it in the
+    /// process.
+    ///
+    /// Reuses the allocation in the same cases as `FatPtrArray::map_label`,
+    /// keeping in mind that the length is stored alongside the label.
+    pub fn map_label<L2, F>(self, func: F) -> ThinPtrArray<E, L2>
+    where
+        F: FnOnce(L) -> L2,
+    {
+        let (data, len) = self.into_base();
+        unsafe {
+            let block = data.as_ptr().data;
+            let label = func(ptr::read(block.lbl_ptr()).label);
+            let data = relabel_block(block, len, LenLabel { len, label });
+            ThinPtrArray::from_base(BaseArray::from_ptr(ThinArrayPtr { data }), len)
+        }
+    }
+}
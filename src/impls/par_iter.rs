@@ -0,0 +1,45 @@
+//! Implements parallel iteration over arrays with `rayon`, when the `rayon`
+//! feature is enabled.
+//!
+//! Only borrowing parallel iterators are provided; they delegate to the
+//! parallel iterators over the array's elements as a slice. `par_iter` and
+//! `par_iter_mut` come from rayon's blanket implementations of
+//! `IntoParallelRefIterator` and `IntoParallelRefMutIterator`.
+//!
+//! ```rust
+//! # use heaparray::*;
+//! use rayon::prelude::*;
+//! let mut array = HeapArray::new(100, |i| i as u64);
+//! array.par_iter_mut().for_each(|x| *x *= 2);
+//! assert_eq!(array.par_iter().sum::<u64>(), 9900);
+//! ```
+
+use super::generic::*;
+use crate::prelude::*;
+use rayon::iter::IntoParallelIterator;
+
+impl<'a, E, L, P> IntoParallelIterator for &'a SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: Sync,
+{
+    type Item = &'a E;
+    type Iter = rayon::slice::Iter<'a, E>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_slice().into_par_iter()
+    }
+}
+
+impl<'a, E, L, P> IntoParallelIterator for &'a mut SafeArray<E, L, P>
+where
+    P: SafeArrayPtr<E, L>,
+    E: Send,
+{
+    type Item = &'a mut E;
+    type Iter = rayon::slice::IterMut<'a, E>;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_slice_mut().into_par_iter()
+    }
+}
@@ -12,6 +12,8 @@ indirections necessary to share data between threads.
 - Atomically reference-counted memory blocks of arbitrary size without
   using a `Vec`; this means you can access reference-counted memory with
   only a single pointer indirection.
+- `no_std` support through the `no-std` feature, which requires the `alloc`
+  crate.
 
 ## Examples
 Creating an array:
@@ -121,8 +123,12 @@ extern crate alloc;
 extern crate const_utils;
 extern crate containers_rs as containers;
 
+#[macro_use]
+mod macros;
+
 mod api;
 pub mod base;
+pub mod error;
 pub mod impls;
 pub mod naive_rc;
 mod traits;
@@ -0,0 +1,49 @@
+//! Contains macros for constructing arrays.
+
+/// Creates a [`HeapArray`](impls/type.FatPtrArray.html) containing the arguments,
+/// similarly to `vec!`.
+///
+/// There are two forms of this macro. The first creates an array containing a
+/// given list of elements, which are moved into the array in order:
+///
+/// ```rust
+/// # #[macro_use] extern crate heaparray;
+/// # use heaparray::*;
+/// # fn main() {
+/// let array = heap_array![1, 2, 3];
+/// assert!(array.len() == 3);
+/// assert!(array[0] == 1 && array[1] == 2 && array[2] == 3);
+/// # }
+/// ```
+///
+/// The second creates an array from a given element and a length; the element
+/// must implement `Clone`, and is cloned into every slot of the array:
+///
+/// ```rust
+/// # #[macro_use] extern crate heaparray;
+/// # use heaparray::*;
+/// # fn main() {
+/// let array = heap_array![String::from("hi"); 3];
+/// assert!(array.len() == 3);
+/// for s in &array {
+///     assert!(s == "hi");
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! heap_array {
+    ($elem:expr; $len:expr) => {{
+        let elem = $elem;
+        <$crate::impls::FatPtrArray<_, ()> as $crate::MakeArray<_>>::new($len, |_| {
+            ::core::clone::Clone::clone(&elem)
+        })
+    }};
+    ($($elem:expr),* $(,)?) => {{
+        let elems = [$($elem),*];
+        let len = elems.len();
+        let mut elems = ::core::iter::IntoIterator::into_iter(elems);
+        <$crate::impls::FatPtrArray<_, ()> as $crate::MakeArray<_>>::new(len, |_| {
+            elems.next().unwrap()
+        })
+    }};
+}
@@ -0,0 +1,160 @@
+//! Contains definition for `AtomicFpArcArray`, a reference to a fat-pointer
+//! array that can be swapped atomically.
+
+use super::types::FpArcArray;
+use crate::prelude::*;
+use crate::traits::rc::{ArrayRef, AtomicArrayRef};
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Reference to an `FpArcArray` that can be swapped atomically between threads.
+///
+/// A fat pointer is two words, so it can't be swapped with a single
+/// `AtomicPtr`; instead, every operation holds a spin lock for as long as it
+/// takes to read or replace the pointer. Arrays that are replaced are dropped
+/// after the lock is released, so the lock is only ever held for a few
+/// instructions, but a thread that gets preempted while holding it stalls every
+/// other thread using the same reference. If that matters more than the size of
+/// the pointer, use a thin-pointer array instead.
+///
+/// The values compared by `compare_exchange` are the ones returned by `as_ref`
+/// and `address_of`. Once every reference to an array is gone, its block can be
+/// reused by another array with the same address, so like with `AtomicPtr`, a
+/// comparison against an address that was read before the array was freed can
+/// succeed. Every operation uses the lock's acquire and release orderings,
+/// regardless of the orderings passed to it.
+///
+/// ```rust
+/// # use heaparray::naive_rc::*;
+/// # use core::sync::atomic::Ordering;
+/// let array_ref = AtomicFpArcArray::new(FpArcArray::new(3, |i| i));
+/// let current = array_ref.as_ref();
+/// let new_ref = AtomicFpArcArray::new(FpArcArray::new(2, |_| 0));
+/// let result = array_ref.compare_exchange(current, new_ref, Ordering::AcqRel, Ordering::Acquire);
+/// assert!(result.is_ok());
+/// assert_eq!(array_ref.load().as_slice(), [0, 0]);
+/// ```
+pub struct AtomicFpArcArray<E, L = ()> {
+    locked: AtomicBool,
+    array: UnsafeCell<FpArcArray<E, L>>,
+}
+
+impl<E, L> AtomicFpArcArray<E, L> {
+    /// Creates a new atomic reference to `array`.
+    pub fn new(array: FpArcArray<E, L>) -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            array: UnsafeCell::new(array),
+        }
+    }
+
+    /// Returns the value that `as_ref` returns while this reference points to
+    /// `array`.
+    pub fn address_of(array: &FpArcArray<E, L>) -> usize {
+        array.get_label() as *const L as usize
+    }
+
+    /// Returns a new reference to the array this reference points to.
+    pub fn load(&self) -> FpArcArray<E, L> {
+        self.with_lock(|array| ArrayRef::clone(array))
+    }
+
+    /// Returns a mutable reference to the array reference inside this one.
+    /// Doesn't need to take the lock, since no other thread can be using it.
+    pub fn get_mut(&mut self) -> &mut FpArcArray<E, L> {
+        unsafe { &mut *self.array.get() }
+    }
+
+    /// Returns the array reference inside this one.
+    pub fn into_inner(self) -> FpArcArray<E, L> {
+        self.array.into_inner()
+    }
+
+    /// Runs `func` on the array reference while holding the lock. `func` must
+    /// not panic, or the lock is never released.
+    fn with_lock<F, T>(&self, func: F) -> T
+    where
+        F: FnOnce(&mut FpArcArray<E, L>) -> T,
+    {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = func(unsafe { &mut *self.array.get() });
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl<E, L> AtomicArrayRef for AtomicFpArcArray<E, L> {
+    fn as_ref(&self) -> usize {
+        self.with_lock(|array| Self::address_of(array))
+    }
+
+    fn compare_and_swap(
+        &self,
+        current: usize,
+        new: Self,
+        order: Ordering,
+    ) -> Result<usize, (Self, usize)> {
+        self.compare_exchange(current, new, order, order)
+    }
+
+    fn compare_exchange(
+        &self,
+        current: usize,
+        new: Self,
+        _success: Ordering,
+        _failure: Ordering,
+    ) -> Result<usize, (Self, usize)> {
+        let mut new = new.into_inner();
+        let prev = self.with_lock(|array| {
+            let prev = Self::address_of(array);
+            if prev == current {
+                mem::swap(array, &mut new);
+            }
+            prev
+        });
+        if prev == current {
+            // `new` now holds the replaced array, which is dropped here, outside
+            // of the lock.
+            Ok(prev)
+        } else {
+            Err((Self::new(new), prev))
+        }
+    }
+
+    fn compare_exchange_weak(
+        &self,
+        current: usize,
+        new: Self,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<usize, (Self, usize)> {
+        self.compare_exchange(current, new, success, failure)
+    }
+
+    fn swap(&self, ptr: Self, _order: Ordering) -> Self {
+        let mut array = ptr.into_inner();
+        self.with_lock(|current| mem::swap(current, &mut array));
+        Self::new(array)
+    }
+}
+
+unsafe impl<E, L> Sync for AtomicFpArcArray<E, L> where FpArcArray<E, L>: Send + Sync {}
+
+impl<E, L> fmt::Debug for AtomicFpArcArray<E, L>
+where
+    E: fmt::Debug,
+    L: fmt::Debug,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_tuple("AtomicFpArcArray")
+            .field(&self.load())
+            .finish()
+    }
+}
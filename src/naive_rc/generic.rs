@@ -3,6 +3,8 @@
 
 use super::ref_counters::*;
 pub use crate::api_prelude_rc::*;
+use crate::error::CapacityError;
+use crate::impls::{FatPtrArray, ThinPtrArray};
 use crate::prelude::*;
 use core::marker::PhantomData;
 use core::mem::ManuallyDrop;
@@ -24,7 +26,7 @@ use core::ptr;
 #[repr(transparent)]
 pub struct RcArray<A, R, E, L = ()>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     data: ManuallyDrop<A>,
@@ -33,7 +35,7 @@ where
 
 impl<A, R, E, L> RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     fn from_ref(ptr: A) -> Self {
@@ -51,22 +53,73 @@ where
     pub fn ref_count(&self) -> usize {
         self.data.get_label().counter()
     }
+    /// Returns the number of strong references to the data this `RcArray` points
+    /// to. Equivalent to `ref_count`, and named to match `RcWeak::strong_count`.
+    pub fn strong_count(&self) -> usize {
+        self.ref_count()
+    }
+    /// Returns whether or not the array this `RcArray` points to has no elements.
+    ///
+    /// An `RcArray` always points to an array, so unlike with a null reference,
+    /// this is only true for arrays of length 0.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Returns whether or not this is the only reference, strong or weak, to the
+    /// data it points to.
+    fn is_unique(&self) -> bool {
+        self.data.get_label().is_unique()
+    }
+    /// Destroys the data this array points to, once its last strong reference
+    /// has been dropped.
+    ///
+    /// If there aren't any weak references to the data, the array is dropped
+    /// normally. Otherwise, the label and elements are dropped in place, and the
+    /// memory block is only deallocated once the weak references are gone too.
+    unsafe fn drop_data(&mut self) {
+        if self.data.get_label().weak_counter() == 1 {
+            ptr::drop_in_place(&mut *self.data);
+        } else {
+            ptr::drop_in_place(self.data.get_label_mut().get_data_mut());
+            self.data.drop_elements();
+            if self.data.get_label().weak_decrement() == 0 {
+                self.data.dealloc_lazy();
+            }
+        }
+    }
     /// Returns an owned version of this array if the caller has exclusive access,
     /// or returns back this reference otherwise.
     pub fn to_owned(self) -> Result<A, Self> {
-        if self.ref_count() > 1 {
-            Err(self)
-        } else {
+        if self.is_unique() {
             Ok(self.to_ref())
+        } else {
+            Err(self)
         }
     }
     /// Returns a mutable reference to the array if the caller has exclusive access,
     /// or `None` otherwise.
     pub fn to_mut(&mut self) -> Option<&mut A> {
-        if self.ref_count() > 1 {
+        if self.is_unique() {
+            Some(&mut *self.data)
+        } else {
             None
+        }
+    }
+    /// Returns a mutable slice into the elements of the array if the caller has
+    /// exclusive access, or `None` otherwise.
+    ///
+    /// Mirrors `std::sync::Arc::get_mut`, and likewise returns `None` while
+    /// there are weak references to the array; use `make_mut` to copy the data
+    /// instead. This is an associated function, so that it doesn't shadow
+    /// `CopyMap::get_mut`; call it as `RcArray::get_mut(&mut array)`.
+    pub fn get_mut(this: &mut Self) -> Option<&mut [E]>
+    where
+        A: SliceArrayMut<E>,
+    {
+        if this.is_unique() {
+            Some(this.data.as_slice_mut())
         } else {
-            Some(&mut *self.data)
+            None
         }
     }
     pub fn ref_eq(&self, other: &Self) -> bool {
@@ -76,16 +129,91 @@ where
 
 impl<A, R, E, L> RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + Clone,
+    A: SplitDropArray<E, R> + IntoIterator<Item = E>,
+    R: RefCounter<L>,
+{
+    /// Returns an iterator that moves the elements out of this array if the
+    /// caller has exclusive access, or returns back this reference otherwise.
+    ///
+    /// Use `iter_cloned` to iterate over owned elements of a shared array.
+    pub fn try_into_iter(self) -> Result<A::IntoIter, Self> {
+        self.to_owned().map(A::into_iter)
+    }
+}
+
+impl<A, R, E, L> RcArray<A, R, E, L>
+where
+    A: SplitDropArray<E, R> + SliceArray<E>,
+    R: RefCounter<L>,
+    E: Clone,
+{
+    /// Returns an iterator over clones of the elements in this array.
+    pub fn iter_cloned(&self) -> core::iter::Cloned<core::slice::Iter<'_, E>> {
+        self.as_slice().iter().cloned()
+    }
+}
+
+impl<E, R, L> RcArray<FatPtrArray<E, R>, R, E, L>
+where
+    R: RefCounter<L>,
+{
+    /// Returns the underlying array if this is the only reference to its data,
+    /// or returns back this reference otherwise.
+    ///
+    /// Unlike `to_owned`, this also removes the reference counter from the
+    /// label of the array. Outstanding weak references count as references to
+    /// the data, so they cause this method to fail as well.
+    pub fn try_unwrap(self) -> Result<FatPtrArray<E, L>, Self> {
+        self.to_owned().map(|array| array.map_label(R::into_data))
+    }
+}
+
+impl<E, R, L> RcArray<ThinPtrArray<E, R>, R, E, L>
+where
+    R: RefCounter<L>,
+{
+    /// Returns the underlying array if this is the only reference to its data,
+    /// or returns back this reference otherwise.
+    ///
+    /// Unlike `to_owned`, this also removes the reference counter from the
+    /// label of the array. Outstanding weak references count as references to
+    /// the data, so they cause this method to fail as well.
+    pub fn try_unwrap(self) -> Result<ThinPtrArray<E, L>, Self> {
+        self.to_owned().map(|array| array.map_label(R::into_data))
+    }
+}
+
+impl<A, R, E, L> RcArray<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    /// Creates a new weak reference to the data this `RcArray` points to.
+    ///
+    /// Weak references don't keep the elements or label of the array alive,
+    /// only its memory block; use `RcWeak::upgrade` to get back a strong
+    /// reference while one still exists.
+    pub fn downgrade(&self) -> RcWeak<A, R, E, L> {
+        self.data.get_label().weak_increment();
+        RcWeak {
+            data: unsafe { mem::transmute_copy(&self.data) },
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<A, R, E, L> RcArray<A, R, E, L>
+where
+    A: SplitDropArray<E, R> + Clone,
     R: RefCounter<L>,
 {
     /// Returns an owned version of this array if the caller has exclusive access,
     /// or copies the data otherwise.
     pub fn make_owned(self) -> A {
-        if self.ref_count() > 1 {
-            (*self.data).clone()
-        } else {
+        if self.is_unique() {
             self.to_ref()
+        } else {
+            (*self.data).clone()
         }
     }
     /// Returns a clone of the data in this array.
@@ -98,19 +226,26 @@ where
             phantom: PhantomData,
         }
     }
-    /// Returns a mutable reference to the array if the caller has exclusive access,
-    /// or copies the data otherwise.
-    pub fn make_mut(&mut self) -> &mut A {
-        if self.ref_count() > 1 {
+    /// Returns a mutable slice into the array if the caller has exclusive access,
+    /// or copies the data (and label) into a new array and returns a mutable
+    /// slice into that otherwise.
+    ///
+    /// Mirrors `std::sync::Arc::make_mut`; other references to the original data
+    /// are unaffected by writes to the returned slice.
+    pub fn make_mut(&mut self) -> &mut [E]
+    where
+        A: SliceArrayMut<E>,
+    {
+        if !self.is_unique() {
             *self = Self::from_ref((*self.data).clone());
         }
-        &mut *self.data
+        self.data.as_slice_mut()
     }
 }
 
 impl<A, R, E, L> Clone for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     fn clone(&self) -> Self {
@@ -122,14 +257,14 @@ where
 
 impl<A, R, E, L> ArrayRef for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
 }
 
 impl<A, R, E, L> Index<usize> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + Index<usize, Output = E>,
+    A: SplitDropArray<E, R> + Index<usize, Output = E>,
     R: RefCounter<L>,
 {
     type Output = E;
@@ -140,22 +275,20 @@ where
 
 impl<A, R, E, L> Drop for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     fn drop(&mut self) {
         let ref_count = self.data.get_label().decrement();
         if ref_count == 0 {
-            unsafe {
-                ptr::drop_in_place(&mut *self.data);
-            }
+            unsafe { self.drop_data() };
         }
     }
 }
 
 impl<A, R, E, L> Container for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     fn len(&self) -> usize {
@@ -165,7 +298,7 @@ where
 
 impl<A, R, E, L> CopyMap<usize, E> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     /// Get a reference into this array. Returns `None` if and only if:
@@ -179,7 +312,7 @@ where
     /// - The array is referenced by another pointer
     /// - The index given is out-of-bounds
     fn get_mut(&mut self, key: usize) -> Option<&mut E> {
-        if self.data.get_label().counter() == 1 {
+        if self.is_unique() {
             self.data.get_mut(key)
         } else {
             None
@@ -192,7 +325,7 @@ where
     /// - The index given is out-of-bounds
     /// - There was nothing in the slot previously
     fn insert(&mut self, key: usize, value: E) -> Option<E> {
-        if self.data.get_label().counter() == 1 {
+        if self.is_unique() {
             self.data.insert(key, value)
         } else {
             None
@@ -202,7 +335,7 @@ where
 
 impl<A, R, E, L> LabelledArray<E, L> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<L>,
 {
     fn with_label<F>(label: L, len: usize, mut func: F) -> Self
@@ -222,9 +355,25 @@ where
     }
 }
 
+impl<A, R, E, L> LabelledArrayRefMut<E, L> for RcArray<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: RefCounter<L>,
+{
+    /// Get a mutable reference to the label of this array. Returns `None` if and
+    /// only if the array is referenced by another pointer.
+    fn get_label_mut(&mut self) -> Option<&mut L> {
+        if self.is_unique() {
+            Some(self.data.get_label_mut().get_data_mut())
+        } else {
+            None
+        }
+    }
+}
+
 impl<A, R, E> MakeArray<E> for RcArray<A, R, E, ()>
 where
-    A: LabelledArray<E, R>,
+    A: SplitDropArray<E, R>,
     R: RefCounter<()>,
 {
     fn new<F>(len: usize, mut func: F) -> Self
@@ -237,18 +386,21 @@ where
 
 impl<A, R, E, L> DefaultLabelledArray<E, L> for RcArray<A, R, E, L>
 where
-    A: DefaultLabelledArray<E, R> + LabelledArray<E, R>,
+    A: DefaultLabelledArray<E, R> + SplitDropArray<E, R>,
     R: RefCounter<L>,
     E: Default,
 {
     fn with_len(label: L, len: usize) -> Self {
         Self::from_ref(A::with_len(R::new(label), len))
     }
+    fn with_len_checked(label: L, len: usize) -> Result<Self, CapacityError> {
+        A::with_len_checked(R::new(label), len).map(Self::from_ref)
+    }
 }
 
 impl<A, R, E, L> SliceArray<E> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E>,
+    A: SplitDropArray<E, R> + SliceArray<E>,
     R: RefCounter<L>,
 {
     fn as_slice(&self) -> &[E] {
@@ -256,9 +408,25 @@ where
     }
 }
 
+impl<A, R, E, L> SliceArrayRef<E> for RcArray<A, R, E, L>
+where
+    A: SplitDropArray<E, R> + SliceArray<E> + SliceArrayMut<E>,
+    R: RefCounter<L>,
+{
+    /// Returns a mutable slice into this array. Returns `None` if and only if
+    /// the array is referenced by another pointer.
+    fn as_slice_mut(&mut self) -> Option<&mut [E]> {
+        if self.is_unique() {
+            Some(self.data.as_slice_mut())
+        } else {
+            None
+        }
+    }
+}
+
 impl<A, R, E, L> Index<Range<usize>> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E>,
+    A: SplitDropArray<E, R> + SliceArray<E>,
     R: RefCounter<L>,
 {
     type Output = [E];
@@ -269,7 +437,7 @@ where
 
 impl<'b, A, R, E, L> IntoIterator for &'b RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E>,
+    A: SplitDropArray<E, R> + SliceArray<E>,
     R: RefCounter<L>,
 {
     type Item = &'b E;
@@ -281,9 +449,9 @@ where
 
 impl<'a, A, R, E, L, A2, R2, E2, L2> PartialEq<RcArray<A2, R2, E2, L2>> for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E> + PartialEq<A2>,
+    A: SplitDropArray<E, R> + SliceArray<E> + PartialEq<A2>,
     R: RefCounter<L>,
-    A2: LabelledArray<E2, R2> + SliceArray<E2>,
+    A2: SplitDropArray<E2, R2> + SliceArray<E2>,
     R2: RefCounter<L2>,
 {
     fn eq(&self, other: &RcArray<A2, R2, E2, L2>) -> bool {
@@ -293,14 +461,14 @@ where
 
 impl<'a, A, R, E, L> Eq for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E> + Eq,
+    A: SplitDropArray<E, R> + SliceArray<E> + Eq,
     R: RefCounter<L>,
 {
 }
 
 impl<A, R, E, L> fmt::Debug for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + SliceArray<E>,
+    A: SplitDropArray<E, R> + SliceArray<E>,
     R: RefCounter<L>,
     E: fmt::Debug,
     L: fmt::Debug,
@@ -320,7 +488,7 @@ where
 
 unsafe impl<A, R, E, L> Send for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + Send + Sync,
+    A: SplitDropArray<E, R> + Send + Sync,
     R: RefCounter<L> + Send + Sync,
     E: Send + Sync,
     L: Send + Sync,
@@ -329,9 +497,99 @@ where
 
 unsafe impl<A, R, E, L> Sync for RcArray<A, R, E, L>
 where
-    A: LabelledArray<E, R> + Send + Sync,
+    A: SplitDropArray<E, R> + Send + Sync,
     R: RefCounter<L> + Send + Sync,
     E: Send + Sync,
     L: Send + Sync,
 {
 }
+
+/// Weak reference to the data of an `RcArray`.
+///
+/// Doesn't keep the elements or label of the array alive, and so can't be used
+/// to access them directly; instead, it can be upgraded back into an `RcArray`
+/// for as long as there's at least one strong reference to the data.
+#[repr(transparent)]
+pub struct RcWeak<A, R, E, L = ()>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    data: ManuallyDrop<A>,
+    phantom: PhantomData<(R, E, L)>,
+}
+
+impl<A, R, E, L> RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    /// Attempts to get a strong reference to the data this weak reference
+    /// points to. Returns `None` if the data has already been dropped.
+    pub fn upgrade(&self) -> Option<RcArray<A, R, E, L>> {
+        if self.data.get_label().try_increment() {
+            Some(RcArray {
+                data: unsafe { mem::transmute_copy(&self.data) },
+                phantom: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+    /// Returns the number of strong references to the data this weak reference
+    /// points to, which is 0 once the data has been dropped.
+    pub fn strong_count(&self) -> usize {
+        self.data.get_label().counter()
+    }
+}
+
+impl<A, R, E, L> Clone for RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    fn clone(&self) -> Self {
+        self.data.get_label().weak_increment();
+        unsafe { mem::transmute_copy(self) }
+    }
+}
+
+impl<A, R, E, L> Drop for RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    fn drop(&mut self) {
+        if self.data.get_label().weak_decrement() == 0 {
+            unsafe { self.data.dealloc_lazy() };
+        }
+    }
+}
+
+impl<A, R, E, L> fmt::Debug for RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R>,
+    R: WeakRefCounter<L>,
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "(Weak)")
+    }
+}
+
+unsafe impl<A, R, E, L> Send for RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R> + Send + Sync,
+    R: WeakRefCounter<L> + Send + Sync,
+    E: Send + Sync,
+    L: Send + Sync,
+{
+}
+
+unsafe impl<A, R, E, L> Sync for RcWeak<A, R, E, L>
+where
+    A: SplitDropArray<E, R> + Send + Sync,
+    R: WeakRefCounter<L> + Send + Sync,
+    E: Send + Sync,
+    L: Send + Sync,
+{
+}
@@ -2,9 +2,11 @@
 //! regular versions; i.e. if you're not careful, you could make a cycle that
 //! never gets deallocated.
 
+mod atomic;
 pub mod generic;
 pub mod ref_counters;
 mod types;
 
 pub use crate::api_prelude_rc::*;
+pub use atomic::*;
 pub use types::*;
@@ -5,7 +5,7 @@
 //! so that the other reference counting structs can just call the API. Since
 //! all functions are `#[inline]`, this ends up being a zero-cost abstraction.
 use core::cell::Cell;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{self, AtomicUsize, Ordering};
 
 /// Utility struct that handles reference counting.
 ///
@@ -24,6 +24,44 @@ pub trait RefCounter<T> {
     fn get_data(&self) -> &T;
     /// Returns a mutable reference to the data associated with this struct.
     fn get_data_mut(&mut self) -> &mut T;
+    /// Consumes this struct, returning the data associated with it.
+    fn into_data(self) -> T
+    where
+        Self: Sized;
+    /// Returns the weak reference count associated with this struct.
+    ///
+    /// All strong references collectively hold one weak reference, so this is
+    /// always at least 1 while there are strong references. Reference counters
+    /// that don't support weak references can rely on the default implementation,
+    /// which always returns 1.
+    fn weak_counter(&self) -> usize {
+        1
+    }
+    /// Decrements the weak reference counter by one and returns its current value.
+    ///
+    /// Reference counters that don't support weak references can rely on the
+    /// default implementation, which always returns 0.
+    fn weak_decrement(&self) -> usize {
+        0
+    }
+    /// Returns whether or not the caller holds the only reference to the data,
+    /// counting both strong and weak references.
+    ///
+    /// The default implementation checks `counter` and then `weak_counter`,
+    /// which is only correct if neither of them can change in between, i.e. for
+    /// reference counters that can't be shared between threads.
+    fn is_unique(&self) -> bool {
+        self.counter() == 1 && self.weak_counter() == 1
+    }
+}
+
+/// Reference counter that also keeps track of weak references.
+pub trait WeakRefCounter<T>: RefCounter<T> {
+    /// Increments the weak reference counter by one and returns its current value.
+    fn weak_increment(&self) -> usize;
+    /// Increments the reference counter by one if it isn't zero, and returns
+    /// whether or not it was incremented.
+    fn try_increment(&self) -> bool;
 }
 
 /// Reference counting struct for non-atomic reference counts.
@@ -71,11 +109,62 @@ impl<T> RefCounter<T> for RcStruct<T> {
     fn get_data_mut(&mut self) -> &mut T {
         &mut self.data
     }
+    fn into_data(self) -> T {
+        self.data
+    }
+}
+
+/// Upper bound on the counts of an `ArcStruct`, past which it aborts the process.
+///
+/// Checking the count after incrementing it leaves a window where other threads
+/// can increment it further before the process is aborted; keeping the bound at
+/// `isize::MAX` leaves room for far more threads than can actually exist.
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+/// Value of the weak count of an `ArcStruct` while `is_unique` has it locked.
+const WEAK_LOCKED: usize = usize::MAX;
+
+/// Aborts the process; used when an atomic reference count overflows, since
+/// panicking would let other threads keep using the overflowed count.
+///
+/// Without `std` there's no `abort` function, so this panics, and then panics
+/// again in a destructor while the first panic is unwinding, which aborts the
+/// same way `alloc` does.
+#[cfg(not(feature = "ref-counter-skip-overflow-check"))]
+#[cold]
+fn abort_overflow() -> ! {
+    #[cfg(not(feature = "no-std"))]
+    std::process::abort();
+    #[cfg(feature = "no-std")]
+    {
+        struct Abort;
+        impl Drop for Abort {
+            fn drop(&mut self) {
+                panic!("reference count of an `ArcStruct` overflowed");
+            }
+        }
+        let _abort = Abort;
+        panic!("reference count of an `ArcStruct` overflowed");
+    }
 }
 
 /// Reference counting struct for atomic reference counts.
+///
+/// # Memory Orderings
+/// Increments use `Relaxed` ordering, since creating a new reference requires
+/// already having one, so there's nothing to synchronize with. Decrements use
+/// `Release` ordering, so that all uses of the data through a reference happen
+/// before the reference is released; the thread that decrements the count to
+/// zero then issues an `Acquire` fence before returning, so that it sees all of
+/// those uses before destroying the data. This is the same scheme as
+/// `std::sync::Arc`.
+///
+/// # Overflow
+/// Unless the `ref-counter-skip-overflow-check` feature is enabled, the process
+/// is aborted if a count exceeds `isize::MAX`.
 pub struct ArcStruct<T> {
     ref_count: AtomicUsize,
+    weak_count: AtomicUsize,
     pub data: T,
 }
 
@@ -92,20 +181,26 @@ impl<T> RefCounter<T> for ArcStruct<T> {
     fn new(data: T) -> Self {
         Self {
             ref_count: AtomicUsize::new(1),
+            weak_count: AtomicUsize::new(1),
             data,
         }
     }
     fn decrement(&self) -> usize {
-        self.ref_count.fetch_sub(1, Ordering::AcqRel) - 1
+        let ref_count = self.ref_count.fetch_sub(1, Ordering::Release) - 1;
+        if ref_count == 0 {
+            atomic::fence(Ordering::Acquire);
+        }
+        ref_count
     }
     fn increment(&self) -> usize {
+        let old_count = self.ref_count.fetch_add(1, Ordering::Relaxed);
         #[cfg(not(feature = "ref-counter-skip-overflow-check"))]
-        assert!(
-            self.counter() < core::usize::MAX,
-            "Incrementing the reference count of an `ArcStruct`\
-             past `core::usize::MAX` is unsafe and results in undefined behavior"
-        );
-        self.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+        {
+            if old_count > MAX_REFCOUNT {
+                abort_overflow();
+            }
+        }
+        old_count + 1
     }
     fn counter(&self) -> usize {
         self.ref_count.load(Ordering::Acquire)
@@ -116,6 +211,95 @@ impl<T> RefCounter<T> for ArcStruct<T> {
     fn get_data_mut(&mut self) -> &mut T {
         &mut self.data
     }
+    fn into_data(self) -> T {
+        self.data
+    }
+    fn weak_counter(&self) -> usize {
+        self.weak_count.load(Ordering::Acquire)
+    }
+    fn weak_decrement(&self) -> usize {
+        let weak_count = self.weak_count.fetch_sub(1, Ordering::Release) - 1;
+        if weak_count == 0 {
+            // Synchronize with the other decrements, so that all accesses through
+            // weak references happen before the memory is deallocated
+            atomic::fence(Ordering::Acquire);
+        }
+        weak_count
+    }
+    /// Locks the weak count by setting it to `usize::MAX` while checking the
+    /// strong count, the same way `std::sync::Arc::is_unique` does, so that
+    /// weak references can't be created, upgraded or dropped in between the
+    /// two checks.
+    fn is_unique(&self) -> bool {
+        // The `Acquire` ordering synchronizes with the `Release` decrement of
+        // any weak reference that was dropped before the lock was taken
+        if self
+            .weak_count
+            .compare_exchange(1, WEAK_LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return false;
+        }
+        let unique = self.ref_count.load(Ordering::Acquire) == 1;
+        self.weak_count.store(1, Ordering::Release);
+        unique
+    }
+}
+
+impl<T> WeakRefCounter<T> for ArcStruct<T> {
+    fn weak_increment(&self) -> usize {
+        let mut weak_count = self.weak_count.load(Ordering::Relaxed);
+        loop {
+            if weak_count == WEAK_LOCKED {
+                core::hint::spin_loop();
+                weak_count = self.weak_count.load(Ordering::Relaxed);
+                continue;
+            }
+            #[cfg(not(feature = "ref-counter-skip-overflow-check"))]
+            {
+                if weak_count > MAX_REFCOUNT {
+                    abort_overflow();
+                }
+            }
+            match self.weak_count.compare_exchange_weak(
+                weak_count,
+                weak_count + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return weak_count + 1,
+                Err(current) => weak_count = current,
+            }
+        }
+    }
+    fn try_increment(&self) -> bool {
+        // Wait while `is_unique` has the weak count locked, so that upgrading
+        // can't race with it
+        while self.weak_count.load(Ordering::Acquire) == WEAK_LOCKED {
+            core::hint::spin_loop();
+        }
+        let mut ref_count = self.ref_count.load(Ordering::Relaxed);
+        loop {
+            if ref_count == 0 {
+                return false;
+            }
+            #[cfg(not(feature = "ref-counter-skip-overflow-check"))]
+            {
+                if ref_count > MAX_REFCOUNT {
+                    abort_overflow();
+                }
+            }
+            match self.ref_count.compare_exchange_weak(
+                ref_count,
+                ref_count + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(current) => ref_count = current,
+            }
+        }
+    }
 }
 
 unsafe impl<T> Send for ArcStruct<T> where T: Send {}
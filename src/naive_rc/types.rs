@@ -1,4 +1,4 @@
-use super::generic::RcArray;
+use super::generic::{RcArray, RcWeak};
 use super::ref_counters::{ArcStruct, RcStruct};
 use crate::impls::{FatPtrArray, ThinPtrArray};
 
@@ -10,6 +10,9 @@ pub type FpArcArray<E, L = ()> = RcArray<FatPtrArray<E, ArcStruct<L>>, ArcStruct
 
 /// Reference counted array, referenced using a fat pointer.
 ///
+/// Uses a non-atomic reference count, so it's cheaper to clone and drop than
+/// its atomic counterpart, but it's neither `Send` nor `Sync`.
+///
 /// See the documentation for `heaparray::naive_rc::generic::RcArray`
 /// for more information on API.
 pub type FpRcArray<E, L = ()> = RcArray<FatPtrArray<E, RcStruct<L>>, RcStruct<L>, E, L>;
@@ -22,6 +25,23 @@ pub type TpArcArray<E, L = ()> = RcArray<ThinPtrArray<E, ArcStruct<L>>, ArcStruc
 
 /// Reference counted array, referenced using a raw pointer.
 ///
+/// Uses a non-atomic reference count, so it's cheaper to clone and drop than
+/// its atomic counterpart, but it's neither `Send` nor `Sync`.
+///
 /// See the documentation for `heaparray::naive_rc::generic::RcArray`
 /// for more information on API.
 pub type TpRcArray<E, L = ()> = RcArray<ThinPtrArray<E, RcStruct<L>>, RcStruct<L>, E, L>;
+
+/// Weak reference to an atomically reference counted array, referenced using a
+/// fat pointer.
+///
+/// See the documentation for `heaparray::naive_rc::generic::RcWeak`
+/// for more information on API.
+pub type FpArcWeak<E, L = ()> = RcWeak<FatPtrArray<E, ArcStruct<L>>, ArcStruct<L>, E, L>;
+
+/// Weak reference to an atomically reference counted array, referenced using a
+/// raw pointer.
+///
+/// See the documentation for `heaparray::naive_rc::generic::RcWeak`
+/// for more information on API.
+pub type TpArcWeak<E, L = ()> = RcWeak<ThinPtrArray<E, ArcStruct<L>>, ArcStruct<L>, E, L>;
@@ -1,5 +1,5 @@
-use super::LabelledArray;
-// use core::sync::atomic::Ordering;
+use super::{LabelledArray, SliceArray};
+use core::sync::atomic::Ordering;
 
 /// A reference to an array, whose clone points to the same data.
 ///
@@ -27,12 +27,25 @@ pub trait ArrayRef: Clone + Sized {
 
 /// Array with optional label struct stored next to the data that can
 /// be conditionally mutated.
+///
+/// Implemented by reference-counted arrays, whose label may be shared with
+/// other references; mutating it is only allowed while there aren't any.
 pub trait LabelledArrayRefMut<E, L>: LabelledArray<E, L> {
-    /// Get mutable reference to the label.
+    /// Get mutable reference to the label, or `None` if it can't be mutated
+    /// right now.
     fn get_label_mut(&mut self) -> Option<&mut L>;
 }
 
-/*
+/// Array reference that can return a slice into its contents, and a mutable
+/// slice while no other references share them.
+///
+/// Immutable slices come from the `SliceArray` supertrait.
+pub trait SliceArrayRef<E>: SliceArray<E> {
+    /// Returns a mutable reference to a slice into this array, or `None` if it
+    /// can't be mutated right now.
+    fn as_slice_mut(&mut self) -> Option<&mut [E]>;
+}
+
 /// Atomically modified array reference.
 ///
 /// Guarrantees that all operations on the
@@ -43,6 +56,8 @@ pub trait LabelledArrayRefMut<E, L>: LabelledArray<E, L> {
 /// For more details on the expected behavior of these methods, see the
 /// documentation for `core::sync::atomic::AtomicPtr`.
 pub trait AtomicArrayRef: Sized {
+    /// Returns the address of the array this reference currently points to,
+    /// for use as the `current` argument of the compare-and-swap methods.
     fn as_ref(&self) -> usize;
     /// Returns the previous value, and also the struct you passed in if the value
     /// wasn't updated
@@ -73,4 +88,4 @@ pub trait AtomicArrayRef: Sized {
     ) -> Result<usize, (Self, usize)>;
     /// Swaps in the specified array reference and returns the previous value
     fn swap(&self, ptr: Self, order: Ordering) -> Self;
-}*/
+}
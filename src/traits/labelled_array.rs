@@ -1,3 +1,6 @@
+use crate::base::MemBlock;
+use crate::error::CapacityError;
+
 /// Array with an optional label struct stored next to the data.
 pub trait LabelledArray<E, L>: containers::CopyMap<usize, E> {
     /// Create a new array, with values initialized using a provided
@@ -25,11 +28,55 @@ pub trait LabelledArrayMut<E, L>: LabelledArray<E, L> {
     unsafe fn get_mut_unchecked(&mut self, idx: usize) -> &mut E;
 }
 
+/// Array whose contents can be destroyed separately from the memory that holds
+/// them.
+///
+/// This allows the memory of an array to outlive its elements and label, which
+/// reference counted arrays need to support weak references.
+///
+/// # Safety
+/// Implementors must guarrantee that after calling `drop_elements`, calling
+/// `dealloc_lazy` frees the memory of the array without accessing its elements,
+/// and that neither method accesses the label.
+pub unsafe trait SplitDropArray<E, L>: LabelledArrayMut<E, L> {
+    /// Runs destructors for the elements of this array, without deallocating it
+    /// or running the destructor of its label.
+    ///
+    /// # Safety
+    /// The elements must not be accessed after calling this method.
+    unsafe fn drop_elements(&mut self);
+
+    /// Deallocates this array without running any destructors.
+    ///
+    /// # Safety
+    /// Neither the elements nor the label may be accessed after calling this
+    /// method, and the array must not be dropped afterwards.
+    unsafe fn dealloc_lazy(&mut self);
+}
+
 /// Trait for a labelled array with a default value.
 pub trait DefaultLabelledArray<E, L>: LabelledArray<E, L>
 where
     E: Default,
 {
     /// Create a new array, initialized to default values.
+    ///
+    /// Every element is initialized by calling `E::default()`. When `E`
+    /// implements `ZeroDefault`, e.g. for integers, `SafeArray::with_len_zeroed`
+    /// asks the allocator for memory that's already zeroed instead.
     fn with_len(label: L, len: usize) -> Self;
+
+    /// Create a new array, initialized to default values, or return an error if
+    /// `len` is too long for the array's memory block instead of panicking.
+    ///
+    /// Use this when the length comes from an untrusted source. The default
+    /// implementation checks against `MemBlock::<E, L>::max_len()`; arrays whose
+    /// blocks hold more than the label, or are padded, should override it.
+    fn with_len_checked(label: L, len: usize) -> Result<Self, CapacityError>
+    where
+        Self: Sized,
+    {
+        MemBlock::<E, L>::check_len(len)?;
+        Ok(Self::with_len(label, len))
+    }
 }
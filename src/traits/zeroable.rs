@@ -0,0 +1,42 @@
+/// Marker trait for types whose all-zeroes bit pattern is a valid value.
+///
+/// Arrays of `Zeroable` elements can be created without initializing each
+/// element individually, by asking the allocator for zeroed memory; see
+/// `SafeArray::new_zeroed`.
+///
+/// # Safety
+/// Implementors must guarrantee that a value of this type consisting entirely
+/// of zero bytes is valid.
+pub unsafe trait Zeroable {}
+
+macro_rules! impl_zeroable {
+    ($($t:ty),*) => {
+        $(unsafe impl Zeroable for $t {})*
+    };
+}
+
+impl_zeroable!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_zeroable!(f32, f64, bool, char, ());
+
+unsafe impl<T> Zeroable for *const T {}
+unsafe impl<T> Zeroable for *mut T {}
+unsafe impl<T, const N: usize> Zeroable for [T; N] where T: Zeroable {}
+
+/// Marker trait for `Zeroable` types whose default value is all zeroes.
+///
+/// Arrays of `ZeroDefault` elements can be initialized to their default values
+/// by asking the allocator for zeroed memory; see `SafeArray::with_len_zeroed`.
+///
+/// # Safety
+/// Implementors must guarrantee that `Self::default()` consists entirely of zero
+/// bytes, and that creating it has no side effects.
+pub unsafe trait ZeroDefault: Zeroable + Default {}
+
+macro_rules! impl_zero_default {
+    ($($t:ty),*) => {
+        $(unsafe impl ZeroDefault for $t {})*
+    };
+}
+
+impl_zero_default!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_zero_default!(f32, f64, bool, char, ());
@@ -1,4 +1,5 @@
 use crate::prelude::*;
+use heaparray::impls::FatPtrArray;
 use heaparray::naive_rc::*;
 
 type TestArray<E, L = ()> = FpRcArray<E, L>;
@@ -42,3 +43,351 @@ fn ref_counting_test() {
     assert!(before_alloc().bytes_alloc == balloc);
     after_alloc(final_ref, t_0);
 }
+
+#[test]
+fn weak_upgrade() {
+    let strong = FpArcArray::<usize>::new(10, |i| i);
+    let weak = strong.downgrade();
+    let upgraded = weak.upgrade().unwrap();
+    assert!(upgraded.ref_eq(&strong));
+    assert_eq!(strong.ref_count(), 2);
+    mem::drop(upgraded);
+    mem::drop(strong);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn weak_drops_elements() {
+    let tracker = std::rc::Rc::new(());
+    let strong = TpArcArray::<std::rc::Rc<()>>::new(100, |_| tracker.clone());
+    let weak = strong.downgrade();
+    let weak_clone = weak.clone();
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 101);
+    mem::drop(strong);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    assert_eq!(weak_clone.strong_count(), 0);
+    mem::drop(weak);
+    assert!(weak_clone.upgrade().is_none());
+}
+
+#[test]
+fn weak_ref_counting() {
+    let info = before_alloc();
+    let strong = FpArcArray::<Load, LabelLoad>::with_len(LabelLoad::default(), LENGTH);
+    let weak = strong.downgrade();
+    let mut strong_copy = weak.upgrade().unwrap();
+    assert!(strong_copy.to_mut().is_none());
+    mem::drop(strong);
+    assert!(strong_copy.to_mut().is_none());
+    mem::drop(strong_copy);
+    after_alloc(weak, info);
+}
+
+#[test]
+fn strong_count() {
+    let first_ref = TpArcArray::<usize>::new(10, |i| i);
+    let weak = first_ref.downgrade();
+    assert_eq!(first_ref.strong_count(), 1);
+    let clones: Vec<_> = (0..3).map(|_| ArrayRef::clone(&first_ref)).collect();
+    assert_eq!(first_ref.strong_count(), 4);
+    assert_eq!(weak.strong_count(), 4);
+    mem::drop(clones);
+    assert_eq!(first_ref.strong_count(), 1);
+    mem::drop(first_ref);
+    assert_eq!(weak.strong_count(), 0);
+}
+
+#[test]
+fn try_unwrap() {
+    let info = before_alloc();
+    let array = FpArcArray::<Load, LabelLoad>::with_len(LabelLoad::default(), LENGTH);
+    let array: FatPtrArray<Load, LabelLoad> = array.try_unwrap().unwrap();
+    assert_eq!(array.len(), LENGTH);
+    after_alloc(array, info);
+
+    let array = TpRcArray::<usize, &str>::with_label("label", 10, |_, i| i);
+    let array = array.try_unwrap().unwrap();
+    assert_eq!(*array.get_label(), "label");
+    assert_eq!(array[9], 9);
+}
+
+#[test]
+fn try_unwrap_shared() {
+    let array = TpArcArray::<usize>::new(10, |i| i);
+    let other = ArrayRef::clone(&array);
+    let array = array.try_unwrap().unwrap_err();
+    assert_eq!(array.strong_count(), 2);
+    mem::drop(other);
+    let weak = array.downgrade();
+    let array = array.try_unwrap().unwrap_err();
+    mem::drop(weak);
+    assert_eq!(array.try_unwrap().unwrap()[3], 3);
+}
+
+#[test]
+fn get_label_mut() {
+    let mut array = FpArcArray::with_label(1, 4, |_, i| i);
+    *array.get_label_mut().unwrap() += 1;
+    let other = ArrayRef::clone(&array);
+    assert!(array.get_label_mut().is_none());
+    mem::drop(other);
+    let weak = array.downgrade();
+    assert!(array.get_label_mut().is_none());
+    mem::drop(weak);
+    assert_eq!(array.get_label_mut(), Some(&mut 2));
+
+    let mut array = TpRcArray::with_label(1, 4, |_, i| i);
+    let other = ArrayRef::clone(&array);
+    assert!(array.get_label_mut().is_none());
+    mem::drop(other);
+    assert_eq!(array.get_label_mut(), Some(&mut 1));
+}
+
+#[test]
+fn get_mut_unique() {
+    let mut array = FpArcArray::new(4, |i| i);
+    FpArcArray::get_mut(&mut array).unwrap()[1] = 10;
+    *array.get_mut(2).unwrap() = 20;
+    let other = ArrayRef::clone(&array);
+    assert!(FpArcArray::get_mut(&mut array).is_none());
+    assert_eq!(other.as_slice(), [0, 10, 20, 3]);
+    mem::drop(other);
+    assert_eq!(FpArcArray::get_mut(&mut array).unwrap(), [0, 10, 20, 3]);
+
+    let mut array = TpArcArray::new(3, |i| i.to_string());
+    let weak = array.downgrade();
+    assert!(TpArcArray::get_mut(&mut array).is_none());
+    mem::drop(weak);
+    TpArcArray::get_mut(&mut array).unwrap()[0].push('!');
+    assert_eq!(array.as_slice(), ["0!", "1", "2"]);
+}
+
+#[test]
+fn slice_array_ref() {
+    let mut array = FpArcArray::new(4, |i| i);
+    array.as_slice_mut().unwrap()[0] = 10;
+    let other = ArrayRef::clone(&array);
+    assert!(array.as_slice_mut().is_none());
+    assert_eq!(array.as_slice(), [10, 1, 2, 3]);
+    mem::drop(other);
+    assert_eq!(array.as_slice_mut().unwrap(), [10, 1, 2, 3]);
+
+    let mut array = TpArcArray::new(4, |i| i);
+    let weak = array.downgrade();
+    assert!(array.as_slice_mut().is_none());
+    mem::drop(weak);
+    array.as_slice_mut().unwrap()[3] = 0;
+    assert_eq!(array.as_slice(), [0, 1, 2, 0]);
+}
+
+#[test]
+fn try_into_iter_unique() {
+    let info = before_alloc();
+    let array = FpArcArray::new(4, |i| i.to_string());
+    let other = ArrayRef::clone(&array);
+    let array = array.try_into_iter().err().unwrap();
+    mem::drop(other);
+    let strings: Vec<String> = array.try_into_iter().unwrap().collect();
+    assert_eq!(strings, ["0", "1", "2", "3"]);
+    after_alloc(strings, info);
+
+    let array = TpRcArray::new(3, |i| i);
+    let iter = array.try_into_iter().unwrap();
+    assert_eq!(iter.rev().collect::<Vec<_>>(), [2, 1, 0]);
+}
+
+#[test]
+fn iter_cloned_shared() {
+    let info = before_alloc();
+    let array = TpArcArray::new(4, |i| i.to_string());
+    let other = ArrayRef::clone(&array);
+    let strings: Vec<String> = other.iter_cloned().collect();
+    assert_eq!(strings, ["0", "1", "2", "3"]);
+    assert_eq!(array.ref_count(), 2);
+    after_alloc((array, other, strings), info);
+}
+
+#[test]
+fn is_empty() {
+    assert!(FpArcArray::<usize>::new(0, |i| i).is_empty());
+    assert!(!FpArcArray::new(2, |i| i).is_empty());
+    assert!(TpArcArray::<usize>::new(0, |i| i).is_empty());
+    let array = TpRcArray::new(1, |i| i);
+    assert!(!ArrayRef::clone(&array).is_empty());
+}
+
+#[test]
+fn make_mut_unique() {
+    let mut array = FpArcArray::<usize>::new(10, |i| i);
+    let ptr = &array[0] as *const usize;
+    array.make_mut()[0] = 100;
+    assert_eq!(&array[0] as *const usize, ptr);
+    assert_eq!(array[0], 100);
+}
+
+#[test]
+fn make_mut_shared() {
+    let info = before_alloc();
+    let mut array = TpArcArray::<Load, LabelLoad>::with_len(LabelLoad::default(), 10);
+    let other = ArrayRef::clone(&array);
+    array.make_mut()[0] = Load::default();
+    assert!(!array.ref_eq(&other));
+    assert_eq!(array.strong_count(), 1);
+    assert_eq!(other.strong_count(), 1);
+    mem::drop(other);
+    after_alloc(array, info);
+
+    let mut array = TpArcArray::<usize>::new(10, |i| i);
+    let other = ArrayRef::clone(&array);
+    array.make_mut()[3] = 0;
+    assert_eq!(array[3], 0);
+    assert_eq!(other[3], 3);
+}
+
+#[test]
+fn rc_ref_counting() {
+    let first_ref = TpRcArray::<usize>::new(10, |i| i);
+    let second_ref = ArrayRef::clone(&first_ref);
+    let third_ref = ArrayRef::clone(&second_ref);
+    assert_eq!(first_ref.strong_count(), 3);
+    mem::drop(second_ref);
+    assert_eq!(first_ref.strong_count(), 2);
+    mem::drop(third_ref);
+    assert_eq!(first_ref.strong_count(), 1);
+}
+
+// Only compiles if `T` doesn't implement `Send`, since otherwise the call to
+// `assert_not_send` is ambiguous.
+trait AmbiguousIfSend<A> {
+    fn assert_not_send() {}
+}
+impl<T: ?Sized> AmbiguousIfSend<()> for T {}
+impl<T: ?Sized + Send> AmbiguousIfSend<u8> for T {}
+
+#[test]
+fn rc_not_send() {
+    <FpRcArray<usize> as AmbiguousIfSend<_>>::assert_not_send();
+    <TpRcArray<usize> as AmbiguousIfSend<_>>::assert_not_send();
+}
+
+#[test]
+fn arc_threaded_ref_counting() {
+    let array = TpArcArray::<usize>::new(LENGTH, |i| i);
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let array = ArrayRef::clone(&array);
+            std::thread::spawn(move || {
+                for _ in 0..1000 {
+                    let clone = ArrayRef::clone(&array);
+                    assert_eq!(clone[LENGTH - 1], LENGTH - 1);
+                    let weak = clone.downgrade();
+                    mem::drop(clone);
+                    assert!(weak.upgrade().is_some());
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(array.strong_count(), 1);
+    assert!(array.try_unwrap().is_ok());
+}
+
+#[test]
+fn arc_threaded_upgrade_get_mut() {
+    use std::sync::{Arc, Barrier};
+
+    let mut array = FpArcArray::<usize>::new(10, |i| i);
+    for _ in 0..1000 {
+        // The other thread always holds either the weak reference or the strong
+        // one it upgrades to, so `get_mut` can never succeed
+        let weak = array.downgrade();
+        let barrier = Arc::new(Barrier::new(2));
+        let thread = {
+            let barrier = Arc::clone(&barrier);
+            std::thread::spawn(move || {
+                barrier.wait();
+                let strong = weak.upgrade().unwrap();
+                mem::drop(weak);
+                strong
+            })
+        };
+        barrier.wait();
+        for _ in 0..10 {
+            assert!(FpArcArray::get_mut(&mut array).is_none());
+        }
+        let strong = thread.join().unwrap();
+        assert!(FpArcArray::get_mut(&mut array).is_none());
+        mem::drop(strong);
+    }
+    assert!(FpArcArray::get_mut(&mut array).is_some());
+}
+
+#[test]
+fn atomic_fat_swap() {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::thread;
+
+    let array_ref = Arc::new(AtomicFpArcArray::new(FpArcArray::new(1, |_| 0)));
+    let threads: Vec<_> = (1..9)
+        .map(|i| {
+            let array_ref = Arc::clone(&array_ref);
+            thread::spawn(move || {
+                let new = AtomicFpArcArray::new(FpArcArray::new(i + 1, |_| i));
+                array_ref.swap(new, Ordering::AcqRel).into_inner()
+            })
+        })
+        .collect();
+    let mut lens: Vec<_> = threads
+        .into_iter()
+        .map(|thread| thread.join().unwrap().len())
+        .collect();
+    lens.push(array_ref.load().len());
+    lens.sort();
+    assert_eq!(lens, (1..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn atomic_fat_compare_exchange() {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::thread;
+
+    let array_ref = Arc::new(AtomicFpArcArray::new(FpArcArray::new(2, |_| 0)));
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let array_ref = Arc::clone(&array_ref);
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    let mut current = array_ref.load();
+                    loop {
+                        let address = AtomicFpArcArray::address_of(&current);
+                        let count = current[0] + 1;
+                        let new = AtomicFpArcArray::new(FpArcArray::new(2, |_| count));
+                        match array_ref.compare_exchange(
+                            address,
+                            new,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                        ) {
+                            Ok(prev) => {
+                                assert_eq!(prev, address);
+                                break;
+                            }
+                            Err((_, prev)) => {
+                                assert_ne!(prev, address);
+                                current = array_ref.load();
+                            }
+                        }
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    assert_eq!(array_ref.load().as_slice(), [800, 800]);
+}
@@ -16,6 +16,33 @@ fn new() {
     after_alloc(array, info);
 }
 
+#[test]
+fn with_label_iter() {
+    let info = before_alloc();
+    let mut array = Array::new(String::from("label"), 5, |label, i| {
+        label.push('!');
+        i.to_string()
+    });
+    assert_eq!(array.get_label(), "label!!!!!");
+    unsafe {
+        for elem in array.iter_mut(5) {
+            elem.push('x');
+        }
+        let elems: Vec<&str> = array.iter(5).map(|s| s.as_str()).collect();
+        assert_eq!(elems, ["0x", "1x", "2x", "3x", "4x"]);
+    }
+    let mut iter = unsafe { array.into_iter(5) };
+    assert_eq!(iter.next().unwrap(), "0x");
+    assert_eq!(iter.next_back().unwrap(), "4x");
+    after_alloc(iter, info);
+
+    let info = before_alloc();
+    let array = Array::new_init(3, |i| vec![i; i]);
+    let elems: Vec<Vec<usize>> = unsafe { array.into_iter(3) }.collect();
+    assert_eq!(elems, [vec![], vec![1], vec![2, 2]]);
+    after_alloc(elems, info);
+}
+
 #[test]
 fn label_element_access() {
     for _ in 0..1000 {
@@ -31,3 +58,32 @@ fn label_element_access() {
         }
     }
 }
+
+#[test]
+fn try_alloc_too_large() {
+    let array = unsafe { Array::<u64, ()>::try_alloc(usize::MAX / 2) };
+    assert!(array.is_none());
+    let mut array = unsafe { Array::<u64, ()>::try_alloc(10) }.unwrap();
+    unsafe { array.drop_lazy(10) };
+}
+
+#[test]
+fn realloc() {
+    let info = before_alloc();
+    let mut array = Array::new(String::from("label"), 10, |_, i| i);
+    unsafe {
+        array.realloc(10, 1000);
+        for i in 10..1000 {
+            core::ptr::write(array.get_ptr_mut(i), i);
+        }
+        assert_eq!(array.get_label(), "label");
+        assert_eq!(
+            array.as_slice(1000),
+            (0..1000).collect::<Vec<_>>().as_slice()
+        );
+        array.realloc(1000, 5);
+        assert_eq!(array.as_slice(5), [0, 1, 2, 3, 4]);
+        array.drop(5);
+    }
+    after_alloc(array, info);
+}
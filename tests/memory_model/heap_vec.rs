@@ -0,0 +1,105 @@
+use crate::prelude::*;
+use heaparray::impls::HeapVec;
+use heaparray::*;
+
+#[test]
+fn push_pop() {
+    let info = before_alloc();
+    let mut vec = HeapVec::with_label_capacity(LabelLoad::default(), 2);
+    assert!(vec.is_empty());
+    for i in 0..100 {
+        vec.push(i.to_string());
+    }
+    assert_eq!(vec.len(), 100);
+    assert!(vec.capacity() >= 100);
+    assert_eq!(vec[42], "42");
+    for i in (0..100).rev() {
+        assert_eq!(vec.pop().unwrap(), i.to_string());
+    }
+    assert!(vec.pop().is_none());
+    assert!(vec.is_empty());
+    after_alloc(vec, info);
+}
+
+#[test]
+fn shrink_to_fit() {
+    let info = before_alloc();
+    let mut vec = HeapVec::with_label_capacity(String::from("label"), 1);
+    for i in 0..100 {
+        vec.push(i.to_string());
+    }
+    for _ in 0..90 {
+        vec.pop();
+    }
+    let grown = vec.heap_bytes();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 10);
+    assert!(vec.heap_bytes() < grown);
+    assert_eq!(vec.get_label(), "label");
+    assert_eq!(vec[9], "9");
+
+    let info_full = before_alloc();
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 10);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info_full);
+    assert_eq!(diff.bytes_alloc, 0);
+    assert_eq!(diff.bytes_dealloc, 0);
+
+    while vec.pop().is_some() {}
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 1);
+    vec.push(String::from("again"));
+    vec.push(String::from("and again"));
+    assert_eq!(vec.as_slice(), ["again", "and again"]);
+    after_alloc(vec, info);
+}
+
+#[test]
+fn extend() {
+    let info = before_alloc();
+    let mut vec = HeapVec::with_capacity(1);
+    vec.extend(0..50);
+    assert_eq!(vec.len(), 50);
+    assert_eq!(vec.capacity(), 50);
+    assert!(vec.as_slice().iter().copied().eq(0..50));
+    vec.extend((50..100).filter(|i| i % 2 == 0));
+    assert_eq!(vec.len(), 75);
+    assert_eq!(vec[74], 98);
+    after_alloc(vec, info);
+
+    let info = before_alloc();
+    let mut vec = HeapVec::with_label_capacity(String::from("label"), 2);
+    vec.push(String::from("a"));
+    vec.extend_from_slice(&[String::from("b"), String::from("c")]);
+    assert_eq!(vec.len(), 3);
+    assert_eq!(vec.as_slice(), ["a", "b", "c"]);
+    assert_eq!(vec.get_label(), "label");
+    after_alloc(vec, info);
+}
+
+#[test]
+fn label() {
+    let mut vec = HeapVec::<usize, Vec<usize>>::with_label(Vec::new(), 10, |label, i| {
+        label.push(i);
+        i
+    });
+    vec.get_label_mut().push(10);
+    assert_eq!(vec.get_label().len(), 11);
+    vec.push(10);
+    assert_eq!(vec.as_slice(), vec.get_label().as_slice());
+}
+
+#[test]
+fn drop_initialized() {
+    let info = before_alloc();
+    let tracker = std::rc::Rc::new(());
+    let mut vec = HeapVec::with_capacity(16);
+    for _ in 0..5 {
+        vec.push(tracker.clone());
+    }
+    mem::drop(vec.pop());
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 5);
+    mem::drop(vec);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    after_alloc(tracker, info);
+}
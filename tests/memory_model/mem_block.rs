@@ -78,3 +78,70 @@ pub fn ref_alloc_efficient() {
 // pub fn block_alignment() {
 //     let blk = MemBlock::<(), Vec<
 // }
+
+#[test]
+pub fn check_len() {
+    use heaparray::error::{AllocError, CapacityError};
+    let max_len = HeapArrayMemBlock::<u64, ()>::max_len();
+    assert_eq!(HeapArrayMemBlock::<u64, ()>::check_len(max_len), Ok(()));
+    let err = HeapArrayMemBlock::<u64, ()>::check_len(max_len + 1).unwrap_err();
+    assert_eq!(
+        err,
+        CapacityError {
+            len: max_len + 1,
+            max_len
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Length {} is invalid: Block cannot be bigger than\
+             core::isize::MAX bytes ({} elements)",
+            max_len + 1,
+            max_len
+        )
+    );
+    assert_eq!(AllocError::from(err), AllocError);
+    assert_eq!(AllocError.to_string(), "Memory allocation failed");
+
+    let err: Box<dyn std::error::Error> = Box::new(err);
+    assert!(err.to_string().starts_with("Length"));
+}
+
+#[test]
+pub fn checked_memory_layout() {
+    type Block = HeapArrayMemBlock<u64, u8>;
+    let max_len = Block::max_len();
+    assert_eq!(
+        Block::checked_memory_layout(10),
+        Some(Block::memory_layout(10))
+    );
+    let (size, align) = Block::checked_memory_layout(max_len).unwrap();
+    assert_eq!((size, align), (8 + max_len * 8, 8));
+    assert!(size <= isize::MAX as usize);
+    assert_eq!(Block::checked_memory_layout(max_len + 1), None);
+    assert_eq!(Block::checked_memory_layout(usize::MAX / 8 + 1), None);
+    assert_eq!(Block::checked_memory_layout(usize::MAX), None);
+    assert_eq!(
+        HeapArrayMemBlock::<(), u8>::checked_memory_layout(usize::MAX),
+        None
+    );
+}
+
+#[test]
+pub fn zero_size_no_alloc() {
+    assert_eq!(HeapArrayMemBlock::<u64, ()>::memory_layout(0).0, 0);
+    let info = before_alloc();
+    let mut blk = unsafe { MemBlock::<u64, ()>::alloc(0) };
+    assert!(!blk.is_null());
+    assert_eq!(blk as usize % mem::align_of::<u64>(), 0);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, 0);
+    unsafe {
+        blk.realloc(0, 4);
+        *blk.elem_ptr(3) = 3;
+        blk.realloc(4, 0);
+        blk.dealloc(0);
+    }
+    after_alloc(blk, info);
+}
@@ -1,3 +1,6 @@
+pub mod array_ref;
 pub mod base_array;
+pub mod heap_vec;
 pub mod mem_block;
+pub mod safe_array;
 pub mod test_utils;
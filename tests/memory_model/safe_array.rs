@@ -0,0 +1,1885 @@
+use crate::prelude::*;
+use heaparray::impls::{FatPtrArray, ThinPtrArray};
+use heaparray::*;
+
+#[test]
+fn split_at() {
+    let info = before_alloc();
+    let array = HeapArray::new(10, |i| i.to_string());
+    let ptrs: Vec<*const u8> = array.as_slice().iter().map(|s| s.as_ptr()).collect();
+    let (left, right) = array.split_at(4);
+    assert!(left.len() == 4 && right.len() == 6);
+    for (i, s) in left.as_slice().iter().chain(right.as_slice()).enumerate() {
+        assert!(*s == i.to_string());
+        assert!(s.as_ptr() == ptrs[i]);
+    }
+    mem::drop(ptrs);
+    after_alloc((left, right), info);
+}
+
+#[test]
+fn split_at_edges() {
+    let info = before_alloc();
+    let (left, right) = HeapArray::new(3, |i| i.to_string()).split_at(0);
+    assert!(left.is_empty() && right.len() == 3);
+    let (left, right) = right.split_at(3);
+    assert!(left.len() == 3 && right.is_empty());
+    after_alloc((left, right), info);
+}
+
+#[test]
+#[should_panic]
+fn split_at_out_of_bounds() {
+    HeapArray::new(3, |i| i).split_at(4);
+}
+
+#[test]
+fn split_at_slice() {
+    let array = HeapArray::new(5, |i| i);
+    let (left, right) = array.split_at_slice(2);
+    assert!(left == [0, 1] && right == [2, 3, 4]);
+}
+
+#[test]
+fn concat() {
+    let info = before_alloc();
+    let first: HeapArray<String> = HeapArray::new(3, |i| i.to_string());
+    let second: HeapArray<String> = HeapArray::new(4, |i| (i + 3).to_string());
+    let array = first.concat(second);
+    assert!(array.len() == 7);
+    for (i, s) in array.as_slice().iter().enumerate() {
+        assert!(*s == i.to_string());
+    }
+    after_alloc(array, info);
+}
+
+#[test]
+fn heap_array_macro() {
+    let array: HeapArray<String> = heap_array![];
+    assert!(array.is_empty());
+    let array = heap_array![String::from("a"), String::from("b"), String::from("c"),];
+    assert!(array.as_slice() == ["a", "b", "c"]);
+    let info = before_alloc();
+    let array = heap_array![String::from("hello"); 5];
+    assert!(array.len() == 5);
+    for s in &array {
+        assert!(s == "hello");
+    }
+    after_alloc(array, info);
+}
+
+#[test]
+fn iter() {
+    let mut array = HeapArray::new(10, |i| i);
+    assert!(array.iter().count() == 10);
+    for (i, elem) in array.iter().enumerate() {
+        assert!(*elem == i);
+    }
+    for elem in array.iter_mut() {
+        *elem *= 2;
+    }
+    let mut thin = ThinPtrArray::new(10, |i| i);
+    thin.iter_mut().for_each(|elem| *elem *= 2);
+    assert!(array.iter().eq(thin.iter()));
+    assert!(array.iter().zip(0..).all(|(elem, i)| *elem == i * 2));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(align(64))]
+struct Aligned(usize);
+
+#[test]
+fn fat_thin_conversion() {
+    let info = before_alloc();
+    let array = HeapArray::with_label(String::from("label"), 10, |_, i| i.to_string());
+    let thin = array.into_thin();
+    assert!(thin.get_label() == "label" && thin.len() == 10);
+    let array = thin.into_fat();
+    assert!(array.get_label() == "label" && array.len() == 10);
+    for (i, s) in array.iter().enumerate() {
+        assert!(*s == i.to_string());
+    }
+    after_alloc(array, info);
+
+    let array = HeapArray::new(4, |i| i as u32);
+    let thin = array.into_thin();
+    assert!(thin.as_slice() == [0, 1, 2, 3]);
+    assert!(thin.into_fat().as_slice() == [0, 1, 2, 3]);
+}
+
+#[test]
+fn fat_thin_conversion_reuses_block() {
+    let array = HeapArray::with_label(7u64, 10, |_, i| Aligned(i));
+    let elems = array.as_slice().as_ptr();
+    let thin = array.into_thin();
+    assert!(thin.as_slice().as_ptr() == elems);
+    assert!(*thin.get_label() == 7);
+    let array = thin.into_fat();
+    assert!(array.as_slice().as_ptr() == elems);
+    assert!(*array.get_label() == 7);
+    assert!(array.iter().zip(0..).all(|(elem, i)| elem.0 == i));
+}
+
+#[test]
+fn boxed_slice_conversion() {
+    let info = before_alloc();
+    let boxed: Box<[String]> = (0..10).map(|i| i.to_string()).collect();
+    let elems = boxed.as_ptr();
+    let array = HeapArray::from(boxed);
+    assert_eq!(array.as_slice().as_ptr(), elems);
+    assert!(array.iter().zip(0..).all(|(s, i)| *s == i.to_string()));
+    let boxed: Box<[String]> = array.into();
+    assert_eq!(boxed.as_ptr(), elems);
+    assert_eq!(boxed[9], "9");
+    after_alloc(boxed, info);
+
+    let info = before_alloc();
+    let empty: Box<[String]> = HeapArray::from(Box::<[String]>::default()).into();
+    assert!(empty.is_empty());
+    let zsts: Box<[()]> = HeapArray::from(vec![(); 5].into_boxed_slice()).into();
+    assert_eq!(zsts.len(), 5);
+    after_alloc((empty, zsts), info);
+}
+
+#[derive(Debug, PartialEq)]
+struct Header {
+    name: String,
+    version: u32,
+}
+
+#[test]
+fn take_label() {
+    let info = before_alloc();
+    let header = Header {
+        name: String::from("header"),
+        version: 3,
+    };
+    let array = HeapArray::with_label(header, 10, |_, i| i.to_string());
+    let (header, array) = array.take_label();
+    assert_eq!(header.name, "header");
+    assert_eq!(header.version, 3);
+    assert_eq!(array.len(), 10);
+    assert!(array.iter().zip(0..).all(|(s, i)| *s == i.to_string()));
+    after_alloc((header, array), info);
+
+    let array = HeapArray::with_label((), 4, |_, i| Aligned(i));
+    let elems = array.as_slice().as_ptr();
+    let ((), array) = array.take_label();
+    assert_eq!(array.as_slice().as_ptr(), elems);
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn send_sync() {
+    use heaparray::impls::{AlignedPtrArray, HeapVec};
+    assert_send_sync::<HeapArray<u8>>();
+    assert_send_sync::<HeapArray<String, Vec<u8>>>();
+    assert_send_sync::<ThinPtrArray<u8>>();
+    assert_send_sync::<AlignedPtrArray<u8, 64>>();
+    assert_send_sync::<HeapVec<u8>>();
+    assert_send_sync::<<HeapArray<String> as IntoIterator>::IntoIter>();
+}
+
+#[test]
+fn send_to_thread() {
+    let array = HeapArray::with_label(String::from("label"), 10, |_, i| i.to_string());
+    let elems = array.as_slice().as_ptr() as usize;
+    let array = std::thread::spawn(move || {
+        assert_eq!(array.get_label(), "label");
+        assert_eq!(array[9], "9");
+        array
+    })
+    .join()
+    .unwrap();
+    assert_eq!(array.as_slice().as_ptr() as usize, elems);
+    let count = std::thread::spawn(move || array.into_iter().filter(|s| s.len() == 1).count());
+    assert_eq!(count.join().unwrap(), 10);
+}
+
+#[test]
+fn clone() {
+    let info = before_alloc();
+    let array = HeapArray::with_label(String::from("label"), 10, |_, i| i.to_string());
+    let copy = array.clone();
+    assert!(array == copy);
+    assert!(array[3].as_ptr() != copy[3].as_ptr());
+    mem::drop(array);
+    after_alloc(copy, info);
+    let array = HeapArray::new(1000, |i| i as u64);
+    assert!(array.clone() == array);
+}
+
+#[test]
+fn clone_copy() {
+    let info = before_alloc();
+    let array = ThinPtrArray::with_label(String::from("label"), 1000, |_, i| i as u64);
+    let copy = array.clone_copy();
+    assert!(copy == array);
+    assert!(copy.as_slice().as_ptr() != array.as_slice().as_ptr());
+    mem::drop(array);
+    after_alloc(copy, info);
+}
+
+#[test]
+fn clone_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+    struct PanicOnClone {
+        tracker: Rc<()>,
+        panics: bool,
+    }
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            assert!(!self.panics);
+            PanicOnClone {
+                tracker: self.tracker.clone(),
+                panics: false,
+            }
+        }
+    }
+
+    let tracker = Rc::new(());
+    let array = HeapArray::with_label(tracker.clone(), 10, |_, i| PanicOnClone {
+        tracker: tracker.clone(),
+        panics: i == 6,
+    });
+    let result = panic::catch_unwind(AssertUnwindSafe(|| array.clone()));
+    assert!(result.is_err());
+    assert_eq!(Rc::strong_count(&tracker), 12);
+}
+
+#[test]
+fn new_zeroed() {
+    let array = HeapArray::<u8>::new_zeroed(1000);
+    assert!(array.len() == 1000);
+    assert!(array.iter().all(|&byte| byte == 0));
+    let array = ThinPtrArray::<u64>::new_zeroed(1000);
+    assert!(array.len() == 1000);
+    assert!(array.iter().all(|&x| x == 0));
+
+    let info = before_alloc();
+    let array = HeapArray::<[u32; 3], _>::with_label_zeroed(String::from("label"), 100);
+    assert!(array.get_label() == "label");
+    assert!(array.iter().all(|elem| *elem == [0; 3]));
+    after_alloc(array, info);
+}
+
+#[test]
+fn with_len_zeroed() {
+    let info = before_alloc();
+    let array = ThinPtrArray::<u64, _>::with_len_zeroed(String::from("label"), 1000);
+    assert!(array.get_label() == "label");
+    assert!(array == ThinPtrArray::with_len(String::from("label"), 1000));
+    after_alloc(array, info);
+    let array = HeapArray::<char>::with_len_zeroed((), 10);
+    assert!(array == HeapArray::with_len((), 10));
+}
+
+#[test]
+fn get_many_mut() {
+    let mut array = HeapArray::new(5, |i| i.to_string());
+    assert!(array.get_many_mut([1, 3, 1]).is_none());
+    assert!(array.get_many_mut([0, 5]).is_none());
+    let [a, b, c] = array.get_many_mut([4, 0, 2]).unwrap();
+    assert!(*a == "4" && *b == "0" && *c == "2");
+    mem::swap(a, b);
+    c.push('!');
+    assert!(array.as_slice() == ["4", "1", "2!", "3", "0"]);
+    assert!(array.get_many_mut([]).is_some());
+}
+
+#[test]
+fn first_last() {
+    let mut array = HeapArray::new(5, |i| i);
+    assert!(array.first() == Some(&0) && array.last() == Some(&4));
+    *array.first_mut().unwrap() = 10;
+    *array.last_mut().unwrap() = 40;
+    assert!(array.as_slice() == [10, 1, 2, 3, 40]);
+
+    let mut thin = ThinPtrArray::new(1, |i| i);
+    assert!(thin.first() == Some(&0) && thin.last() == Some(&0));
+    *thin.last_mut().unwrap() = 3;
+    assert!(thin.first() == Some(&3));
+
+    let mut empty = HeapArray::new(0, |i| i);
+    assert!(empty.first().is_none() && empty.last().is_none());
+    assert!(empty.first_mut().is_none() && empty.last_mut().is_none());
+}
+
+#[test]
+fn truncate() {
+    let info = before_alloc();
+    let array = HeapArray::<String>::new(10, |i| i.to_string());
+    let array = array.truncate(4);
+    assert_eq!(array.len(), 4);
+    assert_eq!(array[3], "3");
+    let array = array.truncate(4).truncate(100);
+    assert_eq!(array.len(), 4);
+    after_alloc(array, info);
+
+    let tracker = std::rc::Rc::new(());
+    let array = ThinPtrArray::<_, &str>::with_label("label", 10, |_, _| tracker.clone());
+    let array = array.truncate(3);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 4);
+    assert_eq!(*array.get_label(), "label");
+    let array = array.truncate(0);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    assert_eq!(array.len(), 0);
+}
+
+#[test]
+fn retain() {
+    let info = before_alloc();
+    let array = HeapArray::with_label("label", 10, |_, i| i.to_string());
+    let ptrs: Vec<*const u8> = array.as_slice().iter().map(|s| s.as_ptr()).collect();
+    let array = array.retain(|s| s.parse::<usize>().unwrap() % 2 == 0);
+    assert_eq!(array.as_slice(), ["0", "2", "4", "6", "8"]);
+    assert_eq!(*array.get_label(), "label");
+    for (i, s) in array.as_slice().iter().enumerate() {
+        assert!(s.as_ptr() == ptrs[i * 2]);
+    }
+    mem::drop(ptrs);
+    after_alloc(array, info);
+
+    let tracker = std::rc::Rc::new(());
+    let array = ThinPtrArray::new(10, |i| (i, tracker.clone()));
+    let array = array.retain(|(i, _)| i % 2 == 0);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 6);
+    assert_eq!(array.len(), 5);
+    let array = array.retain(|_| false);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    assert!(array.is_empty());
+}
+
+#[test]
+fn dedup() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    #[derive(PartialEq)]
+    struct Counted(usize);
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(self.0, Ordering::SeqCst);
+        }
+    }
+
+    let info = before_alloc();
+    let values = [1, 1, 2, 3, 3, 3, 1];
+    let array = ThinPtrArray::with_label("label", 7, |_, i| Counted(values[i]));
+    let array = array.dedup();
+    let kept: Vec<usize> = array.iter().map(|c| c.0).collect();
+    assert_eq!(kept, [1, 2, 3, 1]);
+    assert_eq!(*array.get_label(), "label");
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1 + 3 + 3);
+    mem::drop(kept);
+    after_alloc(array, info);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1 + 3 + 3 + 7);
+
+    let array = HeapArray::<u8>::new(0, |_| 0).dedup();
+    assert!(array.is_empty());
+}
+
+#[test]
+fn drain() {
+    let info = before_alloc();
+    let array = HeapArray::<String>::new(10, |i| i.to_string());
+    let (rest, mut drained) = array.drain(3..6);
+    assert_eq!(rest.len(), 7);
+    assert_eq!(rest[2], "2");
+    assert_eq!(rest[3], "6");
+    assert_eq!(drained.next().unwrap(), "3");
+    let drained: Vec<_> = drained.collect();
+    assert_eq!(drained, ["4", "5"]);
+    mem::drop(drained);
+    after_alloc(rest, info);
+
+    let array = ThinPtrArray::<_, &str>::with_label("label", 10, |_, i| i);
+    let (rest, drained) = array.drain(..);
+    assert_eq!(*rest.get_label(), "label");
+    assert_eq!(rest.len(), 0);
+    assert_eq!(drained.count(), 10);
+}
+
+#[test]
+fn drain_unyielded() {
+    let tracker = std::rc::Rc::new(());
+    let array = HeapArray::new(10, |_| tracker.clone());
+    let (rest, mut drained) = array.drain(2..=8);
+    mem::drop(drained.next());
+    mem::drop(drained);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 4);
+    mem::drop(rest);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+}
+
+#[test]
+#[should_panic]
+fn drain_out_of_bounds() {
+    let array = HeapArray::new(10, |i| i);
+    let _ = array.drain(5..11);
+}
+
+#[test]
+fn partial_into_iter() {
+    let tracker = std::rc::Rc::new(());
+    let array = HeapArray::new(4, |_| tracker.clone());
+    let mut iter = array.into_iter();
+    mem::drop(iter.next());
+    mem::drop(iter);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+
+    let array = HeapArray::new(4, |_| ());
+    assert_eq!(array.into_iter().count(), 4);
+}
+
+#[test]
+fn fused_into_iter() {
+    fn exhaust<I: core::iter::FusedIterator>(mut iter: I, len: usize) {
+        for _ in 0..len {
+            assert!(iter.next().is_some());
+        }
+        for _ in 0..5 {
+            assert!(iter.next().is_none());
+        }
+    }
+
+    exhaust(HeapArray::new(3, |i| i.to_string()).into_iter(), 3);
+    exhaust(ThinPtrArray::new(2, |i| i.to_string()).into_iter(), 2);
+    exhaust(HeapArray::<u8>::new(0, |_| 0).into_iter(), 0);
+}
+
+#[test]
+fn clone_into_iter() {
+    let info = before_alloc();
+    let array = FatPtrArray::with_label(String::from("label"), 5, |_, i| i.to_string());
+    let mut iter = array.into_iter();
+    assert_eq!(iter.next().unwrap(), "0");
+    assert_eq!(iter.next_back().unwrap(), "4");
+    let clone = iter.clone();
+    assert_eq!(clone.len(), 3);
+    let remaining: Vec<String> = iter.collect();
+    let cloned: Vec<String> = clone.collect();
+    assert_eq!(remaining, ["1", "2", "3"]);
+    assert_eq!(cloned, remaining);
+    after_alloc((remaining, cloned), info);
+}
+
+#[test]
+fn into_iter_with_label() {
+    let info = before_alloc();
+    let array = FatPtrArray::with_label(String::from("group"), 4, |_, i| i.to_string());
+    let mut iter = array.into_iter_with_label();
+    assert_eq!(iter.len(), 4);
+    let (label, elem) = iter.next_back().unwrap();
+    assert_eq!((label.as_str(), elem.as_str()), ("group", "3"));
+    let pairs: Vec<(String, String)> = iter.collect();
+    assert_eq!(pairs.len(), 3);
+    for (i, (label, elem)) in pairs.iter().enumerate() {
+        assert_eq!(label, "group");
+        assert_eq!(*elem, i.to_string());
+    }
+    mem::drop((label, elem));
+    after_alloc(pairs, info);
+}
+
+#[test]
+fn double_ended_into_iter() {
+    let info = before_alloc();
+    let array = HeapArray::new(6, |i| i.to_string());
+    let mut iter = array.into_iter();
+    assert_eq!(iter.len(), 6);
+    assert_eq!(iter.next().unwrap(), "0");
+    assert_eq!(iter.next_back().unwrap(), "5");
+    assert_eq!(iter.next_back().unwrap(), "4");
+    assert_eq!(iter.next().unwrap(), "1");
+    assert_eq!(iter.len(), 2);
+    after_alloc(iter, info);
+
+    let array = ThinPtrArray::new(3, |i| i.to_string());
+    let mut iter = array.into_iter();
+    assert_eq!(iter.next_back().unwrap(), "2");
+    assert_eq!(iter.next().unwrap(), "0");
+    assert_eq!(iter.next_back().unwrap(), "1");
+    assert!(iter.next().is_none() && iter.next_back().is_none());
+    assert!(iter.next().is_none());
+    assert_eq!(iter.len(), 0);
+
+    let array = HeapArray::new(5, |i| i);
+    let reversed: Vec<usize> = array.into_iter().rev().collect();
+    assert_eq!(reversed, [4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn into_iter_size_hint() {
+    let array = HeapArray::new(5, |i| i);
+    assert_eq!(array.clone().into_iter().size_hint(), (5, Some(5)));
+    let mut iter = array.into_iter();
+    iter.next();
+    assert_eq!(iter.size_hint(), (4, Some(4)));
+    iter.next_back();
+    assert_eq!(iter.size_hint(), (3, Some(3)));
+    let rest: Vec<usize> = iter.collect();
+    assert_eq!(rest, [1, 2, 3]);
+
+    let array = ThinPtrArray::new(2, |i| i);
+    let (_, drained) = array.drain(..1);
+    assert_eq!(drained.size_hint(), (1, Some(1)));
+}
+
+#[test]
+fn try_from_slice() {
+    use core::convert::TryFrom;
+    let info = before_alloc();
+    let strings = ["a".to_string(), "b".to_string(), "c".to_string()];
+    let array = HeapArray::try_from(&strings[..]).unwrap();
+    assert_eq!(array.as_slice(), strings);
+    mem::drop(strings);
+    after_alloc(array, info);
+
+    let array = ThinPtrArray::<usize>::try_from(&[][..]).unwrap();
+    assert_eq!(array.len(), 0);
+}
+
+#[test]
+fn try_from_slice_panic() {
+    use core::convert::TryFrom;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    struct PanicOnClone(bool);
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            assert!(!self.0);
+            PanicOnClone(false)
+        }
+    }
+    impl Drop for PanicOnClone {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let elems: Vec<_> = (0..5).map(|i| PanicOnClone(i == 3)).collect();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| HeapArray::try_from(&elems[..])));
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn copy_clone_from_slice() {
+    let mut array = HeapArray::<u32>::new(4, |_| 0);
+    array.copy_from_slice(&[1, 2, 3, 4]);
+    assert_eq!(array.as_slice(), [1, 2, 3, 4]);
+
+    let info = before_alloc();
+    let mut array = ThinPtrArray::<String>::new(3, |i| i.to_string());
+    let strings = ["a".to_string(), "b".to_string(), "c".to_string()];
+    array.clone_from_slice(&strings);
+    assert_eq!(array.as_slice(), strings);
+    mem::drop(strings);
+    after_alloc(array, info);
+}
+
+#[test]
+#[should_panic]
+fn copy_from_slice_mismatch() {
+    let mut array = HeapArray::<u32>::new(4, |_| 0);
+    array.copy_from_slice(&[1, 2, 3]);
+}
+
+#[test]
+#[should_panic]
+fn clone_from_slice_mismatch() {
+    let mut array = HeapArray::<String>::new(2, |_| String::new());
+    array.clone_from_slice(&[String::new()]);
+}
+
+#[test]
+fn repeat() {
+    let info = before_alloc();
+    let array = HeapArray::repeat("hello".to_string(), 5);
+    assert_eq!(array.len(), 5);
+    assert!(array.iter().all(|s| s == "hello"));
+    after_alloc(array, info);
+
+    let info = before_alloc();
+    let array = ThinPtrArray::repeat("hello".to_string(), 0);
+    assert_eq!(array.len(), 0);
+    after_alloc(array, info);
+}
+
+#[test]
+fn map() {
+    let info = before_alloc();
+    let array = HeapArray::<u32>::new(10, |i| i as u32);
+    let ptr = array.as_slice().as_ptr() as usize;
+    let array = array.map_in_place(|x| x as i32 - 5);
+    assert_eq!(array.as_slice().as_ptr() as usize, ptr);
+    assert_eq!(array[0], -5);
+    let array = array.map_in_place(|x| x.to_string());
+    assert_eq!(array[9], "4");
+    let array = array.map(|s| s.len());
+    assert_eq!(array[0], 2);
+    after_alloc(array, info);
+
+    let array = ThinPtrArray::<u8, &str>::with_label("label", 10, |_, i| i as u8);
+    let array = array.map_in_place(|x| (x as u64) << 32);
+    assert_eq!(*array.get_label(), "label");
+    assert_eq!(array[1], 1 << 32);
+}
+
+#[test]
+fn map_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+    let tracker = std::rc::Rc::new(());
+    for &in_place in &[true, false] {
+        let array = HeapArray::new(10, |_| tracker.clone());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut count = 0;
+            let f = |rc| {
+                count += 1;
+                assert!(count < 5);
+                rc
+            };
+            if in_place {
+                array.map_in_place(f)
+            } else {
+                array.map(f)
+            }
+        }));
+        assert!(result.is_err());
+        assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    }
+}
+
+#[test]
+fn const_empty() {
+    const EMPTY: FatPtrArray<String> = FatPtrArray::empty();
+    static STATIC_EMPTY: HeapArray<u64> = FatPtrArray::empty();
+    assert!(STATIC_EMPTY.is_empty());
+    assert_eq!(STATIC_EMPTY.as_slice(), []);
+
+    let info = before_alloc();
+    let mut array = EMPTY;
+    assert_eq!(array.len(), 0);
+    assert!(array.iter_mut().next().is_none());
+    assert_eq!(
+        array.as_slice().as_ptr() as usize % mem::align_of::<String>(),
+        0
+    );
+    let array = array.concat(HeapArray::new(1, |_| String::from("a")));
+    assert_eq!(array.as_slice(), ["a"]);
+    mem::drop(array);
+    after_alloc(EMPTY, info);
+}
+
+#[test]
+fn fixed_size_array_conversions() {
+    use core::convert::TryFrom;
+
+    let info = before_alloc();
+    let array = HeapArray::from([String::from("a"), String::from("b")]);
+    assert_eq!(array.as_slice(), ["a", "b"]);
+    let array = <[String; 3]>::try_from(array).unwrap_err();
+    assert_eq!(array.as_slice(), ["a", "b"]);
+    let elements = <[String; 2]>::try_from(array).unwrap();
+    assert_eq!(elements, ["a", "b"]);
+    after_alloc(elements, info);
+
+    let tracker = std::rc::Rc::new(());
+    let array = ThinPtrArray::new(4, |_| tracker.clone());
+    let array = <[std::rc::Rc<()>; 5]>::try_from(array).unwrap_err();
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 5);
+    let elements = <[std::rc::Rc<()>; 4]>::try_from(array).unwrap();
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 5);
+    mem::drop(elements);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+
+    let empty: [u8; 0] = TryFrom::try_from(HeapArray::from([])).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn new_with() {
+    use std::time::Instant;
+
+    let start = Instant::now();
+    let array = HeapArray::new_with(5, Instant::now, |i| i * 2);
+    assert!(start <= *array.get_label() && *array.get_label() <= Instant::now());
+    assert_eq!(array.as_slice(), [0, 2, 4, 6, 8]);
+
+    let info = before_alloc();
+    let array = ThinPtrArray::new_with(3, || String::from("label"), |i| i.to_string());
+    assert_eq!(array.get_label(), "label");
+    assert_eq!(array.as_slice(), ["0", "1", "2"]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn try_with_label() {
+    let info = before_alloc();
+    let array = HeapArray::<String, Vec<usize>>::try_with_label(Vec::new(), 10, |label, i| {
+        label.push(i);
+        Ok::<_, ()>(i.to_string())
+    })
+    .unwrap();
+    assert_eq!(array.get_label().len(), 10);
+    assert_eq!(array[9], "9");
+    after_alloc(array, info);
+
+    let tracker = std::rc::Rc::new(());
+    let result = ThinPtrArray::try_with_label(tracker.clone(), 10, |_, i| {
+        if i == 5 {
+            Err(i)
+        } else {
+            Ok(tracker.clone())
+        }
+    });
+    assert_eq!(result.unwrap_err(), 5);
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+}
+
+#[test]
+fn with_label_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    struct Counted;
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        ThinPtrArray::with_label(Counted, 10, |_, i| {
+            assert!(i != 3);
+            Counted
+        })
+    }));
+    assert!(result.is_err());
+    // The label and exactly 3 elements
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 4);
+}
+
+#[test]
+fn drop_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    struct PanicOnDrop(bool);
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+            assert!(!self.0);
+        }
+    }
+
+    let array = HeapArray::with_label(PanicOnDrop(false), 10, |_, i| PanicOnDrop(i == 3));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| mem::drop(array)));
+    assert!(result.is_err());
+    // The label and every element, including the one that panicked
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 11);
+
+    let array = ThinPtrArray::with_label(PanicOnDrop(true), 5, |_, _| PanicOnDrop(false));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| mem::drop(array)));
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 17);
+
+    let array = HeapArray::new(5, |i| PanicOnDrop(i == 4));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut iter = array.into_iter();
+        mem::drop(iter.next());
+        mem::drop(iter);
+    }));
+    assert!(result.is_err());
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 22);
+}
+
+#[test]
+fn aligned() {
+    use heaparray::impls::AlignedPtrArray;
+    let info = before_alloc();
+    let array = AlignedPtrArray::<u8, 64>::new(100, |i| i as u8);
+    assert_eq!(array.as_slice().as_ptr() as usize % 64, 0);
+    assert_eq!(array[99], 99);
+    let clone = array.clone();
+    assert_eq!(clone.as_slice().as_ptr() as usize % 64, 0);
+    mem::drop(clone);
+    after_alloc(array, info);
+
+    let info = before_alloc();
+    let array =
+        AlignedPtrArray::<u64, 4096, String>::with_label("label".to_string(), 10, |_, i| i as u64);
+    assert_eq!(array.as_slice().as_ptr() as usize % 4096, 0);
+    assert_eq!(array.get_label(), "label");
+    after_alloc(array, info);
+}
+
+#[test]
+fn uninit() {
+    use core::mem::MaybeUninit;
+    let info = before_alloc();
+    let mut array = ThinPtrArray::<MaybeUninit<String>, &str>::with_label_uninit("label", 10);
+    let ptr = array.as_slice().as_ptr() as usize;
+    for i in 0..array.len() {
+        unsafe { core::ptr::write(array[i].as_mut_ptr(), i.to_string()) };
+    }
+    let array = unsafe { array.assume_init() };
+    assert_eq!(array.as_slice().as_ptr() as usize, ptr);
+    assert_eq!(*array.get_label(), "label");
+    assert_eq!(array[7], "7");
+    after_alloc(array, info);
+}
+
+#[test]
+fn thin_set_len() {
+    use core::mem::MaybeUninit;
+    let info = before_alloc();
+    let mut array = ThinPtrArray::<MaybeUninit<String>, &str>::with_label_uninit("label", 10);
+    for i in 0..4 {
+        unsafe { core::ptr::write(array[i].as_mut_ptr(), i.to_string()) };
+    }
+    let array = unsafe {
+        array.set_len(4);
+        array.assume_init()
+    };
+    assert_eq!(array.len(), 4);
+    assert_eq!(*array.get_label(), "label");
+    assert!(array
+        .iter()
+        .map(|s| s.as_str())
+        .eq(vec!["0", "1", "2", "3"]));
+    after_alloc(array, info);
+}
+
+#[test]
+#[should_panic(expected = "Length 5 is greater than the allocated length 4")]
+fn thin_set_len_too_long() {
+    let mut array = ThinPtrArray::<u8>::new(4, |_| 0);
+    unsafe { array.set_len(5) };
+}
+
+#[test]
+fn range_index() {
+    let mut array = HeapArray::new(10, |i| i);
+    assert_eq!(array[1..3], [1, 2]);
+    assert_eq!(array[..2], [0, 1]);
+    assert_eq!(array[..=2], [0, 1, 2]);
+    assert_eq!(array[8..], [8, 9]);
+    assert_eq!(array[8..=9], [8, 9]);
+    assert_eq!(array[..].len(), 10);
+    assert!(array[5..5].is_empty());
+    array[..3].copy_from_slice(&[5, 5, 5]);
+    assert_eq!(array[..4], [5, 5, 5, 3]);
+}
+
+#[test]
+#[should_panic(expected = "array of length 10")]
+fn range_index_out_of_bounds() {
+    let array = ThinPtrArray::new(10, |i| i);
+    let _ = &array[5..11];
+}
+
+#[test]
+#[should_panic(expected = "Range 0..=18446744073709551615 is out of bounds")]
+#[cfg(target_pointer_width = "64")]
+fn range_index_inclusive_overflow() {
+    let array = HeapArray::new(10, |i| i);
+    let _ = &array[..=usize::MAX];
+}
+
+#[test]
+#[should_panic(expected = "is out of bounds for array of length 10")]
+fn drain_inclusive_overflow() {
+    let array = HeapArray::new(10, |i| i);
+    let _ = array.drain(..=usize::MAX);
+}
+
+#[test]
+#[should_panic(expected = "is out of bounds for array of length 10")]
+fn drain_excluded_start_overflow() {
+    use core::ops::Bound;
+    let array = HeapArray::new(10, |i| i);
+    let _ = array.drain((Bound::Excluded(usize::MAX), Bound::Unbounded));
+}
+
+#[test]
+fn heap_bytes() {
+    use heaparray::base::MemBlock;
+    use heaparray::impls::AlignedPtrArray;
+    for &len in &[0, 1, 7, 100] {
+        let array = HeapArray::new(len, |_| 0u8);
+        assert_eq!(array.heap_bytes(), MemBlock::<u8, ()>::memory_layout(len).0);
+        let array = HeapArray::with_label(1u16, len, |_, _| 0u64);
+        assert_eq!(
+            array.heap_bytes(),
+            MemBlock::<u64, u16>::memory_layout(len).0
+        );
+        let array = HeapArray::new(len, |i| i.to_string());
+        assert_eq!(
+            array.heap_bytes(),
+            MemBlock::<String, ()>::memory_layout(len).0
+        );
+        let array = ThinPtrArray::new(len, |i| i as u32);
+        assert_eq!(
+            array.heap_bytes(),
+            MemBlock::<u32, usize>::memory_layout(len).0
+        );
+        let array = AlignedPtrArray::<u8, 64>::new(len, |_| 0);
+        assert_eq!(
+            array.heap_bytes(),
+            MemBlock::<u8, ()>::memory_layout_aligned(len, 64).0
+        );
+    }
+}
+
+#[test]
+fn swap_with() {
+    let info = before_alloc();
+    let mut left = HeapArray::new(3, |i| i.to_string());
+    let mut right = ThinPtrArray::with_label(0u8, 2, |_, i| format!("right {}", i));
+    left.swap_with(2, &mut right, 0);
+    assert_eq!(left.as_slice(), ["0", "1", "right 0"]);
+    assert_eq!(right.as_slice(), ["2", "right 1"]);
+    left.swap_with(2, &mut right, 0);
+    assert_eq!(right.as_slice(), ["right 0", "right 1"]);
+    after_alloc((left, right), info);
+}
+
+#[test]
+#[should_panic(expected = "Index 2 is out of bounds for array of length 2")]
+fn swap_with_out_of_bounds() {
+    let mut left = HeapArray::new(3, |i| i.to_string());
+    let mut right = HeapArray::new(2, |i| i.to_string());
+    left.swap_with(1, &mut right, 2);
+}
+
+#[test]
+fn copy_within() {
+    let mut array = HeapArray::new(6, |i| i);
+    array.copy_within(0..4, 2);
+    assert_eq!(array.as_slice(), [0, 1, 0, 1, 2, 3]);
+    array.copy_within(2.., 0);
+    assert_eq!(array.as_slice(), [0, 1, 2, 3, 2, 3]);
+    array.copy_within(1..1, 6);
+    array.copy_within(..=1, 4);
+    assert_eq!(array.as_slice(), [0, 1, 2, 3, 0, 1]);
+
+    let mut array = ThinPtrArray::new(4, |i| i as u8);
+    array.copy_within(1..3, 1);
+    assert_eq!(array.as_slice(), [0, 1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "array of length 6")]
+fn copy_within_out_of_bounds() {
+    let mut array = HeapArray::new(6, |i| i);
+    array.copy_within(0..3, 4);
+}
+
+#[test]
+fn swap_with_slice() {
+    let info = before_alloc();
+    let mut array = HeapArray::new(4, |i| i as u32);
+    let mut vec: Vec<u32> = vec![10, 11, 12, 13];
+    array.swap_with_slice(&mut vec);
+    assert_eq!(array.as_slice(), [10, 11, 12, 13]);
+    assert_eq!(vec, [0, 1, 2, 3]);
+    mem::drop(vec);
+
+    let mut strings = ThinPtrArray::new(2, |i| i.to_string());
+    let mut other = ["a".to_string(), "b".to_string()];
+    strings.swap_with_slice(&mut other);
+    assert_eq!(strings.as_slice(), ["a", "b"]);
+    assert_eq!(other, ["0", "1"]);
+    mem::drop(other);
+    after_alloc((array, strings), info);
+}
+
+#[test]
+#[should_panic(expected = "array of length 4")]
+fn swap_with_slice_length_mismatch() {
+    let mut array = HeapArray::new(4, |i| i);
+    array.swap_with_slice(&mut [0, 1, 2]);
+}
+
+#[test]
+fn set_label() {
+    #[derive(Debug, PartialEq)]
+    struct Label {
+        name: String,
+        count: usize,
+    }
+
+    let info = before_alloc();
+    let label = |name: &str, count| Label {
+        name: name.to_string(),
+        count,
+    };
+    let mut array = ThinPtrArray::with_label(label("first", 1), 5, |_, i| i);
+    let old = array.set_label(label("second", 2));
+    assert_eq!(old, label("first", 1));
+    assert_eq!(*array.get_label(), label("second", 2));
+    assert_eq!(array.len(), 5);
+    assert_eq!(array.as_slice(), [0, 1, 2, 3, 4]);
+
+    let mut fat = HeapArray::with_label(label("first", 1), 2, |_, i| i);
+    fat.set_label(label("third", 3));
+    assert_eq!(fat.get_label().count, 3);
+    mem::drop(old);
+    after_alloc((array, fat), info);
+}
+
+#[test]
+fn is_empty() {
+    assert!(HeapArray::<usize>::new(0, |i| i).is_empty());
+    assert!(!HeapArray::new(1, |i| i).is_empty());
+    assert!(ThinPtrArray::<usize>::new(0, |i| i).is_empty());
+    assert!(!ThinPtrArray::new(3, |i| i).is_empty());
+    let (left, right) = HeapArray::new(2, |i| i).split_at(2);
+    assert!(!left.is_empty() && right.is_empty());
+}
+
+#[test]
+fn default() {
+    #[derive(Default)]
+    struct Holder {
+        fat: FatPtrArray<String>,
+        thin: ThinPtrArray<String>,
+    }
+
+    let info = before_alloc();
+    let holder = Holder::default();
+    assert!(holder.fat.is_empty() && holder.thin.is_empty());
+    assert_eq!(FatPtrArray::<i32>::default().len(), 0);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, holder.thin.heap_bytes());
+    after_alloc(holder, info);
+}
+
+#[test]
+fn chunks_and_windows() {
+    let mut array = HeapArray::new(10, |i| i);
+    let chunks: Vec<&[usize]> = array.chunks(3).collect();
+    assert_eq!(chunks.len(), 4);
+    assert_eq!(chunks[0], [0, 1, 2]);
+    assert_eq!(chunks[3], [9]);
+
+    for chunk in array.chunks_mut(4) {
+        chunk[0] = 0;
+    }
+    assert_eq!(array.as_slice(), [0, 1, 2, 3, 0, 5, 6, 7, 0, 9]);
+
+    let sums: Vec<usize> = array.windows(2).map(|w| w[0] + w[1]).collect();
+    assert_eq!(sums, [1, 3, 5, 3, 5, 11, 13, 7, 9]);
+    assert_eq!(array.windows(11).count(), 0);
+}
+
+#[test]
+fn split() {
+    let bytes = b"let x = 1;;y";
+    let array: HeapArray<u8> = HeapArray::new(bytes.len(), |i| bytes[i]);
+    let pieces: Vec<&[u8]> = array.split(|&b| b == b';').collect();
+    assert_eq!(pieces, [&b"let x = 1"[..], b"", b"y"]);
+    let pieces: Vec<&[u8]> = array.rsplit(|&b| b == b' ').collect();
+    assert_eq!(pieces, [&b"1;;y"[..], b"=", b"x", b"let"]);
+}
+
+#[test]
+fn into_label() {
+    #[derive(Default)]
+    struct Counts {
+        even: usize,
+        odd: usize,
+    }
+
+    let info = before_alloc();
+    let tracker = std::rc::Rc::new(());
+    let array = ThinPtrArray::with_label(Counts::default(), 9, |counts, i| {
+        if i % 2 == 0 {
+            counts.even += 1;
+        } else {
+            counts.odd += 1;
+        }
+        tracker.clone()
+    });
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 10);
+    let counts = array.into_label();
+    assert_eq!((counts.even, counts.odd), (5, 4));
+    assert_eq!(std::rc::Rc::strong_count(&tracker), 1);
+    after_alloc(tracker, info);
+
+    let info = before_alloc();
+    let array = HeapArray::with_label(String::from("label"), 3, |_, i| i.to_string());
+    let label = array.into_label();
+    assert_eq!(label, "label");
+    after_alloc(label, info);
+}
+
+#[test]
+#[cfg(feature = "bytemuck")]
+fn bytes_round_trip() {
+    let mut array = HeapArray::new(5, |i| (i as u32) << 24 | 0xab);
+    assert_eq!(array.as_bytes().len(), 5 * mem::size_of::<u32>());
+    let copy = HeapArray::<u32>::from_bytes(array.as_bytes());
+    assert_eq!(copy, array);
+
+    array.as_bytes_mut()[..4].copy_from_slice(&9u32.to_ne_bytes());
+    assert_eq!(array[0], 9);
+    let bytes: Vec<u8> = std::iter::once(0)
+        .chain(array.as_bytes().to_vec())
+        .collect();
+    let copy = ThinPtrArray::<u32>::from_bytes(&bytes[1..]);
+    assert_eq!(copy, array);
+    assert!(HeapArray::<u32>::from_bytes(&[]).is_empty());
+}
+
+#[test]
+#[cfg(feature = "bytemuck")]
+#[should_panic(expected = "Byte slice of length 6 can't be split into elements of size 4")]
+fn from_bytes_bad_length() {
+    HeapArray::<u32>::from_bytes(&[0; 6]);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn par_iter() {
+    use rayon::prelude::*;
+
+    let mut array = HeapArray::new(100_000, |i| i as u64);
+    array.par_iter_mut().for_each(|x| *x *= *x);
+    assert!(array.iter().enumerate().all(|(i, &x)| x == (i * i) as u64));
+    let thin = ThinPtrArray::with_label("label", 1000, |_, i| i as u64);
+    assert_eq!(thin.par_iter().sum::<u64>(), 499_500);
+}
+
+#[test]
+fn entry() {
+    let array = ThinPtrArray::with_label("{:x}", 3, |_, i| i * 10);
+    assert_eq!(array.entry(2), (&"{:x}", &20));
+    let (label, elem) = array.entry(0);
+    assert!(core::ptr::eq(label, array.get_label()));
+    assert!(core::ptr::eq(elem, &array[0]));
+}
+
+#[test]
+#[should_panic(expected = "Index 3 is out of bounds for array of length 3")]
+fn entry_out_of_bounds() {
+    HeapArray::with_label(0u8, 3, |_, i| i).entry(3);
+}
+
+#[test]
+fn iter_with_label() {
+    let array = ThinPtrArray::with_label(String::from("label"), 4, |_, i| i * 2);
+    let label: *const String = array.get_label();
+    let mut iter = array.iter_with_label();
+    assert_eq!(iter.len(), 4);
+    assert_eq!(iter.next_back().map(|(_, &e)| e), Some(6));
+    for (i, (l, &e)) in iter.enumerate() {
+        assert!(core::ptr::eq(l, label));
+        assert_eq!(e, i * 2);
+    }
+    assert_eq!(HeapArray::<u8, ()>::default().iter_with_label().count(), 0);
+}
+
+#[test]
+fn reverse_iteration() {
+    let array = ThinPtrArray::new(5, |i| i.to_string());
+    let reversed: Vec<&str> = array.iter_rev().map(|s| s.as_str()).collect();
+    assert_eq!(reversed, ["4", "3", "2", "1", "0"]);
+
+    let array = HeapArray::new(8, |i| i);
+    let chunks: Vec<&[usize]> = array.rchunks(3).collect();
+    assert_eq!(chunks, [&[5, 6, 7][..], &[2, 3, 4], &[0, 1]]);
+}
+
+#[test]
+fn chunks_exact() {
+    let mut array = HeapArray::<f32>::new(10, |i| i as f32);
+    let chunks = array.chunks_exact(4);
+    assert_eq!(chunks.remainder(), [8.0, 9.0]);
+    let sums: Vec<f32> = chunks.map(|c| c.iter().sum()).collect();
+    assert_eq!(sums, [6.0, 22.0]);
+
+    let mut chunks = array.chunks_exact_mut(4);
+    for chunk in &mut chunks {
+        chunk[3] = 0.0;
+    }
+    chunks.into_remainder()[1] = -1.0;
+    assert_eq!(
+        array.as_slice(),
+        [0.0, 1.0, 2.0, 0.0, 4.0, 5.0, 6.0, 0.0, 8.0, -1.0]
+    );
+}
+
+#[test]
+#[should_panic]
+fn chunks_zero_size() {
+    let array = ThinPtrArray::new(10, |i| i);
+    let _ = array.chunks(0);
+}
+
+#[test]
+fn clear_to_default() {
+    let info = before_alloc();
+    let mut array = HeapArray::with_label(String::from("label"), 4, |_, i| vec![i as u8; 16]);
+    let block = array.as_slice().as_ptr();
+    let before_clear = before_alloc();
+    array.clear_to_default();
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&before_clear);
+    assert_eq!(diff.bytes_alloc, 0);
+    assert_eq!(diff.bytes_dealloc, 4 * 16);
+    assert!(array.iter().all(|v| v.is_empty() && v.capacity() == 0));
+    assert_eq!(array.as_slice().as_ptr(), block);
+    assert_eq!(array.get_label(), "label");
+    after_alloc(array, info);
+}
+
+#[test]
+fn with_len_checked() {
+    type Large = [u64; 32];
+    let max_len = heaparray::base::MemBlock::<Large, ()>::max_len();
+    let info = before_alloc();
+    let result = FatPtrArray::<Large, ()>::with_len_checked((), max_len + 1);
+    let err = result.unwrap_err();
+    assert_eq!(err.len, max_len + 1);
+    assert_eq!(err.max_len, max_len);
+    let array = FatPtrArray::<u8, ()>::with_len_checked((), 4).unwrap();
+    assert_eq!(array.as_slice(), [0; 4]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn with_len_checked_block_overhead() {
+    use heaparray::base::MemBlock;
+    use heaparray::impls::AlignedPtrArray;
+    use heaparray::naive_rc::FpArcArray;
+    // Thin arrays store their length in the block, aligned arrays pad the label,
+    // and reference counted arrays store their counts, so all of them have a
+    // smaller maximum length than a plain block with the same label
+    let max_len = MemBlock::<u64, ()>::max_len();
+    let err = ThinPtrArray::<u64, ()>::with_len_checked((), max_len).unwrap_err();
+    assert_eq!(err.len, max_len);
+    assert!(err.max_len < max_len);
+    assert!(FpArcArray::<u64>::with_len_checked((), max_len).is_err());
+    let max_len = MemBlock::<u64, u8>::max_len();
+    assert!(AlignedPtrArray::<u64, 4096, u8>::with_len_checked(0, max_len).is_err());
+
+    let array = ThinPtrArray::<u64, ()>::with_len_checked((), 4).unwrap();
+    assert_eq!(array.as_slice(), [0; 4]);
+    let array = AlignedPtrArray::<u64, 4096, u8>::with_len_checked(0, 4).unwrap();
+    assert_eq!(array.as_slice(), [0; 4]);
+}
+
+#[test]
+fn cursor() {
+    use heaparray::impls::Cursor;
+    let info = before_alloc();
+    let mut array = FatPtrArray::new(4, |_| String::new());
+    let mut cursor = Cursor::new(&mut array);
+    for i in 0..4 {
+        assert!(cursor.write(i.to_string()));
+    }
+    assert_eq!(cursor.remaining(), 0);
+    assert!(!cursor.write(String::from("extra")));
+    assert_eq!(cursor.read(), None);
+
+    cursor.seek(0);
+    assert_eq!(cursor.remaining(), 4);
+    let mut read = Vec::new();
+    while let Some(elem) = cursor.read() {
+        read.push(elem.clone());
+    }
+    assert_eq!(read, ["0", "1", "2", "3"]);
+    cursor.seek(2);
+    assert_eq!(cursor.position(), 2);
+    assert_eq!(cursor.read().unwrap(), "2");
+    mem::drop(read);
+    after_alloc(array, info);
+}
+
+#[test]
+#[should_panic(expected = "Index 5 is out of bounds for array of length 4")]
+fn cursor_seek_out_of_bounds() {
+    let mut array = HeapArray::new(4, |i| i);
+    heaparray::impls::Cursor::new(&mut array).seek(5);
+}
+
+#[test]
+fn iter_downcast() {
+    use core::any::Any;
+    let info = before_alloc();
+    let array: HeapArray<Box<dyn Any>> = HeapArray::new(6, |i| match i % 3 {
+        0 => Box::new(i as u32) as Box<dyn Any>,
+        1 => Box::new(i.to_string()),
+        _ => Box::new(()),
+    });
+    let numbers: Vec<u32> = array.iter_downcast::<u32>().flatten().copied().collect();
+    assert_eq!(numbers, [0, 3]);
+    let strings: Vec<Option<&String>> = array.iter_downcast().collect();
+    assert_eq!(strings.len(), 6);
+    assert_eq!(strings[1].unwrap(), "1");
+    assert_eq!(strings[4].unwrap(), "4");
+    assert!(strings.iter().step_by(3).all(|s| s.is_none()));
+    assert_eq!(
+        array.iter_downcast::<()>().rev().position(|e| e.is_some()),
+        Some(0)
+    );
+    assert!(array.iter_downcast::<u8>().all(|elem| elem.is_none()));
+    mem::drop((numbers, strings));
+    after_alloc(array, info);
+}
+
+#[test]
+fn label_and_slice_mut() {
+    let info = before_alloc();
+    let mut array = ThinPtrArray::with_label(vec![3, 1, 2], 3, |_, i| i.to_string());
+    let (order, elements) = array.label_and_slice_mut();
+    for (elem, &i) in elements.iter_mut().zip(order) {
+        elem.push_str(&i.to_string());
+    }
+    elements.swap(0, order[1]);
+    assert_eq!(array.as_slice(), ["11", "03", "22"]);
+    assert_eq!(*array.get_label(), [3, 1, 2]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn forget_contents() {
+    use std::rc::Rc;
+    let drops = Rc::new(());
+    let info = before_alloc();
+    let array = ThinPtrArray::with_label(Rc::clone(&drops), 5, |_, _| Rc::clone(&drops));
+    assert_eq!(Rc::strong_count(&drops), 7);
+    let mut moved = Vec::with_capacity(5);
+    for elem in array.iter() {
+        moved.push(unsafe { core::ptr::read(elem) });
+    }
+    array.forget_contents();
+    assert_eq!(Rc::strong_count(&drops), 6);
+    mem::drop(moved);
+    assert_eq!(Rc::strong_count(&drops), 1);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, diff.bytes_dealloc);
+}
+
+#[test]
+fn builder() {
+    let info = before_alloc();
+    let mut builder = FatPtrArray::builder(String::from("label"), 5);
+    builder.set(3, String::from("3"));
+    builder.set(0, String::from("zero"));
+    builder.set(0, String::from("0"));
+    assert_eq!(builder.initialized(), 2);
+    assert!(builder.is_set(3) && !builder.is_set(1));
+    builder.push(String::from("1")).unwrap();
+    builder.push(String::from("2")).unwrap();
+    let mut builder = builder.finish().unwrap_err();
+    builder.push(String::from("4")).unwrap();
+    assert_eq!(builder.push(String::from("5")).unwrap_err(), "5");
+    let array = builder.finish().unwrap();
+    assert_eq!(array.get_label(), "label");
+    assert_eq!(array.as_slice(), ["0", "1", "2", "3", "4"]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn builder_drop_partial() {
+    use std::rc::Rc;
+    let drops = Rc::new(());
+    let info = before_alloc();
+    let mut builder = FatPtrArray::builder(Rc::clone(&drops), 6);
+    builder.set(4, Rc::clone(&drops));
+    builder.push(Rc::clone(&drops)).unwrap();
+    assert_eq!(Rc::strong_count(&drops), 4);
+    mem::drop(builder);
+    assert_eq!(Rc::strong_count(&drops), 1);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, diff.bytes_dealloc);
+}
+
+#[test]
+fn builder_set_panicking_drop() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    struct PanicOnDrop {
+        _tracker: Rc<()>,
+        panics: bool,
+    }
+    impl Drop for PanicOnDrop {
+        fn drop(&mut self) {
+            assert!(!self.panics);
+        }
+    }
+
+    let drops = Rc::new(());
+    let elem = |panics| PanicOnDrop {
+        _tracker: Rc::clone(&drops),
+        panics,
+    };
+
+    let mut builder = FatPtrArray::builder((), 2);
+    builder.set(0, elem(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| builder.set(0, elem(false))));
+    assert!(result.is_err());
+    assert_eq!(builder.initialized(), 1);
+    assert_eq!(Rc::strong_count(&drops), 2);
+    mem::drop(builder);
+    assert_eq!(Rc::strong_count(&drops), 1);
+}
+
+#[test]
+fn take() {
+    let info = before_alloc();
+    let mut array = ThinPtrArray::new(3, |i| i.to_string());
+    let taken = array.take(2);
+    assert_eq!(taken, "2");
+    assert!(array[2].is_empty());
+    assert_eq!(array.as_slice(), ["0", "1", ""]);
+    mem::drop(taken);
+    after_alloc(array, info);
+}
+
+#[test]
+fn insert() {
+    let info = before_alloc();
+    let mut array = ThinPtrArray::<String>::new(3, |i| i.to_string());
+    assert_eq!(array.insert(2, "two".to_string()).unwrap(), "2");
+    assert_eq!(array.insert(0, "zero".to_string()).unwrap(), "0");
+    assert!(array.insert(3, "three".to_string()).is_none());
+    assert_eq!(array.as_slice(), ["zero", "1", "two"]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn atomic_ops() {
+    use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+
+    let array = HeapArray::new(4, |_| AtomicUsize::new(0));
+    std::thread::scope(|scope| {
+        for t in 0..4 {
+            let array = &array;
+            scope.spawn(move || {
+                for i in 0..1000 {
+                    array.fetch_add(t, i, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    for t in 0..4 {
+        assert_eq!(array.load(t, Ordering::Relaxed), 499_500);
+    }
+
+    let array = ThinPtrArray::new(2, |_| AtomicIsize::new(5));
+    array.store(1, -3, Ordering::Relaxed);
+    assert_eq!(array.fetch_sub(0, 7, Ordering::Relaxed), 5);
+    assert_eq!(array.load(0, Ordering::Relaxed), -2);
+    assert_eq!(array.load(1, Ordering::Relaxed), -3);
+}
+
+#[test]
+fn atomic_cas() {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let array = HeapArray::new(2, |_| AtomicU64::new(0));
+    let successes = std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let array = &array;
+                scope.spawn(move || {
+                    let mut successes = 0;
+                    for _ in 0..1000 {
+                        let current = array.load(1, Ordering::Relaxed);
+                        let result =
+                            array.cas(1, current, current + 1, Ordering::AcqRel, Ordering::Relaxed);
+                        successes += result.is_ok() as u64;
+                    }
+                    successes
+                })
+            })
+            .collect();
+        threads.into_iter().map(|t| t.join().unwrap()).sum::<u64>()
+    });
+    assert_eq!(array.load(1, Ordering::Relaxed), successes);
+    assert_eq!(array.load(0, Ordering::Relaxed), 0);
+
+    assert_eq!(
+        array.cas(0, 0, 5, Ordering::SeqCst, Ordering::SeqCst),
+        Ok(0)
+    );
+    assert_eq!(
+        array.cas(0, 0, 7, Ordering::SeqCst, Ordering::SeqCst),
+        Err(5)
+    );
+}
+
+#[test]
+fn as_ptr() {
+    let mut array = HeapArray::new(3, |i| i as u64);
+    assert_eq!(array.as_ptr(), &array[0] as *const u64);
+    unsafe { *array.as_mut_ptr().add(2) = 7 };
+    assert_eq!(array.as_slice(), [0, 1, 7]);
+
+    let mut thin = ThinPtrArray::with_label(1u8, 2, |_, i| i as u32);
+    assert_eq!(thin.as_ptr(), &thin[0] as *const u32);
+    assert_eq!(thin.as_mut_ptr(), &mut thin[0] as *mut u32);
+
+    let mut empty = ThinPtrArray::<u64, u8>::with_label(0, 0, |_, _| 0);
+    assert!(!empty.as_ptr().is_null());
+    assert_eq!(empty.as_mut_ptr() as usize % mem::align_of::<u64>(), 0);
+}
+
+#[test]
+fn get_unchecked() {
+    let mut array = ThinPtrArray::new(3, |i| i * 10);
+    unsafe {
+        assert_eq!(*array.get_unchecked(2), 20);
+        *array.get_unchecked_mut(0) = 5;
+    }
+    assert_eq!(array.as_slice(), [5, 10, 20]);
+}
+
+#[test]
+#[cfg(not(feature = "no-asserts"))]
+fn index_out_of_bounds_message() {
+    use std::panic::{self, AssertUnwindSafe};
+
+    fn message(result: std::thread::Result<()>) -> String {
+        *result.err().unwrap().downcast::<String>().unwrap()
+    }
+
+    let mut array = HeapArray::new(3, |i| i);
+    let mut thin = ThinPtrArray::new(4, |i| i);
+    let messages = [
+        message(panic::catch_unwind(|| {
+            let _ = &array[5];
+        })),
+        message(panic::catch_unwind(AssertUnwindSafe(|| array[7] = 0))),
+        message(panic::catch_unwind(|| {
+            let _ = &thin[6];
+        })),
+        message(panic::catch_unwind(AssertUnwindSafe(|| thin[4] = 0))),
+    ];
+    for (msg, (len, idx)) in messages.iter().zip(&[(3, 5), (3, 7), (4, 6), (4, 4)]) {
+        assert!(msg.contains(&format!("the len is {}", len)), "{}", msg);
+        assert!(msg.contains(&format!("the index is {}", idx)), "{}", msg);
+    }
+}
+
+#[test]
+#[cfg(feature = "no-asserts")]
+fn index_no_asserts() {
+    let mut array = HeapArray::new(3, |i| i);
+    let mut thin = ThinPtrArray::new(3, |i| i);
+    array[1] = 7;
+    thin[1] = 7;
+    assert_eq!(array[1] + thin[2], 9);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Index 3 is out of bounds for array of length 3")]
+fn get_unchecked_out_of_bounds() {
+    let array = HeapArray::new(3, |i| i);
+    unsafe { array.get_unchecked(3) };
+}
+
+#[test]
+#[should_panic(expected = "Index 3 is out of bounds for array of length 3")]
+fn take_out_of_bounds() {
+    let mut array = HeapArray::new(3, |i| i.to_string());
+    array.take(3);
+}
+
+#[test]
+fn raw_parts() {
+    let info = before_alloc();
+    let array = HeapArray::with_label("label".to_string(), 5, |_, i| i.to_string());
+    let (block, len) = array.into_raw_parts();
+    assert_eq!(len, 5);
+    let array = unsafe { HeapArray::<String, String>::from_raw_parts(block, len) };
+    assert_eq!(array.get_label(), "label");
+    assert_eq!(array.as_slice(), ["0", "1", "2", "3", "4"]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn leak() {
+    let slice: &'static mut [String] = ThinPtrArray::new(3, |i| i.to_string()).leak();
+    slice[1].push('!');
+    assert_eq!(slice, ["0", "1!", "2"]);
+
+    let slice = HeapArray::with_label(vec![1, 2], 2, |label, i| label[i]).leak();
+    slice.swap(0, 1);
+    assert_eq!(slice, [2, 1]);
+}
+
+#[test]
+fn clone_with_label() {
+    #[derive(Debug, PartialEq)]
+    struct Handle(usize);
+
+    let info = before_alloc();
+    let array = ThinPtrArray::with_label(Handle(1), 4, |_, i| i.to_string());
+    let clone = array.clone_with_label(Handle(2));
+    assert_eq!(*clone.get_label(), Handle(2));
+    assert_eq!(clone.as_slice(), array.as_slice());
+    assert_eq!(*array.get_label(), Handle(1));
+    after_alloc((array, clone), info);
+}
+
+#[test]
+fn map_label() {
+    #[derive(Debug, PartialEq)]
+    struct Meta {
+        name: String,
+        id: u64,
+    }
+
+    let info = before_alloc();
+    let array = HeapArray::new(4, |i| i as u64).map_label(|()| 5u8);
+    let ptr = array.as_slice().as_ptr();
+    let array = array.map_label(u64::from);
+    // Both labels take up the 8 bytes before the elements, so the block is reused
+    assert_eq!(array.as_slice().as_ptr(), ptr);
+    let array = array.map_label(|id| Meta {
+        name: "meta".to_string(),
+        id,
+    });
+    assert_eq!(array.get_label().id, 5);
+    assert_eq!(array.as_slice(), [0, 1, 2, 3]);
+    let array = array.map_label(|meta| assert_eq!(meta.name, "meta"));
+    assert_eq!(array.as_slice(), [0, 1, 2, 3]);
+    after_alloc(array, info);
+
+    let info = before_alloc();
+    let array = ThinPtrArray::new(3, |i| i.to_string());
+    let array = array.map_label(|()| vec![1, 2, 3]);
+    assert_eq!(array.get_label().len(), 3);
+    assert_eq!(array.len(), 3);
+    let array = array.map_label(mem::drop);
+    assert_eq!(array.as_slice(), ["0", "1", "2"]);
+    after_alloc(array, info);
+}
+
+#[test]
+fn contains() {
+    let array = HeapArray::new(5, |i| i * 2);
+    assert!(array.contains(&4));
+    assert!(!array.contains(&5));
+    let array = ThinPtrArray::new(3, |i| i.to_string());
+    assert!(array.contains(&"2".to_string()));
+    assert!(!ThinPtrArray::<usize>::default().contains(&0));
+}
+
+#[test]
+fn position() {
+    let array = HeapArray::new(6, |i| i % 3);
+    assert_eq!(array.position(|&x| x == 2), Some(2));
+    assert_eq!(array.rposition(|&x| x == 2), Some(5));
+    assert_eq!(array.position(|&x| x == 3), None);
+    assert_eq!(array.rposition(|&x| x == 3), None);
+    let array = ThinPtrArray::new(3, |i| i.to_string());
+    assert_eq!(array.rposition(|s| s.len() == 1), Some(2));
+}
+
+#[test]
+fn split_first_last() {
+    let mut array = HeapArray::<usize>::default();
+    assert!(array.split_first().is_none() && array.split_last().is_none());
+    assert!(array.split_first_mut().is_none() && array.split_last_mut().is_none());
+
+    let array = HeapArray::new(1, |i| i);
+    assert_eq!(array.split_first(), Some((&0, &[][..])));
+    assert_eq!(array.split_last(), Some((&0, &[][..])));
+
+    let mut array = ThinPtrArray::new(4, |i| i);
+    assert_eq!(array.split_first(), Some((&0, &[1, 2, 3][..])));
+    assert_eq!(array.split_last(), Some((&3, &[0, 1, 2][..])));
+    let (first, rest) = array.split_first_mut().unwrap();
+    *first = rest.iter().sum();
+    let (last, rest) = array.split_last_mut().unwrap();
+    *last = rest[0];
+    assert_eq!(array.as_slice(), [6, 1, 2, 6]);
+}
+
+#[test]
+fn zero_sized_elements() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    #[derive(Clone)]
+    struct Zst;
+    impl Drop for Zst {
+        fn drop(&mut self) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let info = before_alloc();
+    let array = HeapArray::<(), ()>::new(1000, |_| ());
+    assert_eq!(array.len(), 1000);
+    assert_eq!(array[999], ());
+    assert_eq!(array.iter().count(), 1000);
+    assert_eq!(array.clone().into_iter().count(), 1000);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, 0);
+    after_alloc(array, info);
+
+    let info = before_alloc();
+    let array = ThinPtrArray::new(1000, |_| Zst);
+    let clone = array.clone();
+    let mut iter = clone.into_iter();
+    assert_eq!(iter.len(), 1000);
+    mem::drop(iter.next());
+    mem::drop(iter.next_back());
+    mem::drop(iter);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 1000);
+    after_alloc(array, info);
+    assert_eq!(DROPPED.load(Ordering::SeqCst), 2000);
+}
+
+#[test]
+fn zero_sized_label() {
+    for &len in &[0, 1, 3, 64, 1000] {
+        assert_eq!(HeapArray::new(len, |_| 0u8).heap_bytes(), len);
+        assert_eq!(HeapArray::new(len, |_| 0u64).heap_bytes(), 8 * len);
+        assert_eq!(
+            HeapArray::with_label((), len, |_, _| 0u16).heap_bytes(),
+            2 * len
+        );
+    }
+    assert_eq!(MemBlock::<u8, ()>::memory_layout(5), (5, 1));
+    assert_eq!(MemBlock::<u64, ()>::memory_layout(5), (40, 8));
+}
+
+#[test]
+fn io_write() {
+    use std::io::{ErrorKind, Write};
+
+    let mut buffer = HeapArray::with_label(0, 16, |_, _| 0u8);
+    buffer.write_all(b"GET ").unwrap();
+    buffer.write_all(b"/index").unwrap();
+    write!(buffer, "{}", 42).unwrap();
+    assert_eq!(*buffer.get_label(), 12);
+    assert_eq!(&buffer[..12], b"GET /index42");
+    assert_eq!(buffer.write(b"long tail").unwrap(), 4);
+    assert_eq!(&buffer[..], b"GET /index42long");
+    assert_eq!(buffer.write(b"more").unwrap(), 0);
+    let err = buffer.write_all(b"more").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WriteZero);
+
+    let mut buffer = ThinPtrArray::with_label(0, 0, |_, _| 0u8);
+    assert_eq!(buffer.write(b"x").unwrap(), 0);
+    buffer.flush().unwrap();
+}
+
+#[test]
+fn eq_slices() {
+    let array = HeapArray::new(3, |i| i + 1);
+    assert_eq!(array, vec![1, 2, 3]);
+    assert_eq!(array, &[1, 2, 3][..]);
+    assert_eq!(vec![1, 2, 3], array);
+    assert_eq!(&[1, 2, 3][..], array);
+    assert!(array == *vec![1, 2, 3].as_slice());
+    assert!(array != vec![1, 2]);
+    assert!(array != vec![1, 2, 3, 4]);
+    assert!([1, 2, 4][..] != array);
+    assert!(Vec::<usize>::new() != array);
+
+    let strings = ThinPtrArray::new(2, |i| i.to_string());
+    assert_eq!(strings, vec!["0", "1"]);
+    assert!(ThinPtrArray::<usize>::default() == Vec::<usize>::new());
+}
+
+#[test]
+fn eq_different_lengths() {
+    let array = HeapArray::new(3, |i| i);
+    let (left, _) = array.clone().split_at(2);
+    assert!(left != array);
+    assert!(array != left);
+}
+
+#[test]
+fn clone_into() {
+    let array = HeapArray::with_label(7, 100, |_, i| i as u64);
+    let mut target = HeapArray::with_label(0, 100, |_, _| 0);
+    let block = target.as_slice().as_ptr();
+    let info = before_alloc();
+    array.clone_into(&mut target);
+    let diff = crate::TEST_MONITOR.local_info().relative_to(&info);
+    assert_eq!(diff.bytes_alloc, 0);
+    assert_eq!(target, array);
+    assert_eq!(target.as_slice().as_ptr(), block);
+
+    let mut target = ThinPtrArray::new(1, |i| i.to_string());
+    let array = ThinPtrArray::new(3, |i| i.to_string());
+    array.clone_into(&mut target);
+    assert_eq!(target, array);
+}
+
+#[test]
+fn clone_from_resizes() {
+    use heaparray::impls::AlignedPtrArray;
+    let info = before_alloc();
+    let mut target = FatPtrArray::with_label(String::from("old"), 2, |_, i| i.to_string());
+    for &len in &[5, 5, 1, 0, 3, 8, 2] {
+        let source = FatPtrArray::with_label(len.to_string(), len, |_, i| (i * len).to_string());
+        target.clone_from(&source);
+        assert_eq!(target.len(), len);
+        assert_eq!(*target.get_label(), len.to_string());
+        assert_eq!(target, source);
+    }
+    after_alloc(target, info);
+
+    let info = before_alloc();
+    let mut target = ThinPtrArray::with_label(String::from("old"), 6, |_, i| i.to_string());
+    for &len in &[2, 9, 9, 0, 4] {
+        let source = ThinPtrArray::with_label(len, len, |_, i| i.to_string());
+        let mut source = source.map_label(|len| len.to_string());
+        source[0..len].reverse();
+        target.clone_from(&source);
+        assert_eq!(target.len(), len);
+        assert_eq!(target, source);
+    }
+    after_alloc(target, info);
+
+    let mut target = AlignedPtrArray::<u8, 64>::new(3, |i| i as u8);
+    for &len in &[10, 1] {
+        let source = AlignedPtrArray::<u8, 64>::new(len, |i| i as u8 + 1);
+        target.clone_from(&source);
+        assert_eq!(target.as_slice().as_ptr() as usize % 64, 0);
+        assert_eq!(target, source);
+    }
+}
+
+#[test]
+fn clone_from_panic() {
+    use std::panic::{self, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    struct PanicOnClone(Rc<()>, bool);
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            assert!(!self.1);
+            PanicOnClone(Rc::clone(&self.0), false)
+        }
+    }
+
+    let drops = Rc::new(());
+    let mut target = HeapArray::new(1, |_| PanicOnClone(Rc::clone(&drops), false));
+    let source = HeapArray::new(4, |i| PanicOnClone(Rc::clone(&drops), i == 2));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| target.clone_from(&source)));
+    assert!(result.is_err());
+    assert_eq!(target.len(), 2);
+    assert_eq!(Rc::strong_count(&drops), 7);
+    mem::drop((source, target));
+    assert_eq!(Rc::strong_count(&drops), 1);
+}
@@ -0,0 +1,22 @@
+//! Smoke test for the `no-std` feature; run it with
+//! `cargo test --features no-std --test no_std`.
+//!
+//! The test harness itself needs `std`, but everything else here only uses
+//! `core` and `heaparray`.
+#![cfg(feature = "no-std")]
+#![no_std]
+
+extern crate heaparray;
+extern crate std;
+
+use heaparray::*;
+
+#[test]
+fn construct_and_index() {
+    let mut array = HeapArray::new(10, |i| i * 2);
+    array[0] = 100;
+    assert_eq!(array[0], 100);
+    assert_eq!(array[9], 18);
+    let array = ArcArray::new(10, |i| i as u8);
+    assert_eq!(array[3], 3);
+}